@@ -1,10 +1,115 @@
-use std::{ time::Instant, fs, vec };
+use std::{ time::Instant, fs, vec, ops::Range, fmt, env };
 use lib::get_part;
 
 // first custom typing
 /** (from, to, range len) */
 type Mapping = (usize, usize, usize);
 
+/** every layer squashed into one function, sorted by `from` */
+#[derive(Debug, PartialEq)]
+struct Piecewise {
+    pieces: Vec<Mapping>,
+}
+
+impl Piecewise {
+    // x -> x for everything
+    fn identity() -> Self {
+        Self { pieces: vec![(0, 0, usize::MAX)] }
+    }
+
+    // feed the output of every piece through another layer of the almanac
+    fn then(&self, map: &[Mapping]) -> Self {
+        let mut pieces = vec![];
+
+        for (to, from, len) in self.pieces.iter() {
+            let (to, from, len) = (*to, *from, *len);
+            // (out start, out end) that haven't hit a mapping yet
+            let mut pending = vec![(to, to + len)];
+
+            for (y, x, map_len) in map.iter() {
+                let (y, x, map_len) = (*y, *x, *map_len);
+                let mut missed = vec![];
+
+                for (a, b) in pending {
+                    let start = a.max(x);
+                    let end = b.min(x + map_len);
+
+                    if start >= end {
+                        missed.push((a, b));
+                        continue;
+                    }
+
+                    // where this overlap came from originally
+                    pieces.push((start - x + y, start - to + from, end - start));
+
+                    if a < start {
+                        missed.push((a, start));
+                    }
+                    if end < b {
+                        missed.push((end, b));
+                    }
+                }
+
+                pending = missed;
+            }
+
+            // unmapped parts pass straight through
+            for (a, b) in pending {
+                pieces.push((a, a - to + from, b - a));
+            }
+        }
+
+        pieces.sort_by_key(|(_, from, _)| *from);
+
+        Self { pieces }
+    }
+
+    // swap to/from; fails if two pieces land on the same numbers, since
+    // then there's more than one way back (the puzzle's almanacs are fine)
+    fn inverse(&self) -> Result<Self, String> {
+        let mut pieces: Vec<Mapping> = self.pieces
+            .iter()
+            .map(|(to, from, len)| (*from, *to, *len))
+            .collect();
+
+        pieces.sort_by_key(|(_, from, _)| *from);
+
+        // sorted, so any overlap shows up between neighbours
+        for w in pieces.windows(2) {
+            let ((_, a, a_len), (_, b, b_len)) = (w[0], w[1]);
+
+            if a + a_len > b {
+                return Err(format!("{}..{} comes from more than one place", b, (a + a_len).min(b + b_len)));
+            }
+        }
+
+        Ok(Self { pieces })
+    }
+
+    fn get(&self, n: usize) -> Option<usize> {
+        // first piece that starts after n; we want the one before it
+        let i = self.pieces.partition_point(|(_, from, _)| *from <= n);
+
+        if i == 0 {
+            return None;
+        }
+
+        x_to_y_map(n, &self.pieces[i - 1])
+    }
+}
+
+impl fmt::Display for Piecewise {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>20} {:>20} {:>20}", "from", "to", "len")?;
+
+        for (to, from, len) in self.pieces.iter() {
+            writeln!(f, "{:>20} {:>20} {:>20}", from, to, len)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 struct Almanac {
     seeds: Vec<usize>,
@@ -55,6 +160,8 @@ impl Almanac {
         Almanac { seeds, seed_location }
     }
 
+    // layer by layer; the tests check `composed` against this
+    #[cfg(test)]
     fn seed_to_location(&self, seed: usize) -> usize {
         let mut cur = seed;
         'outer: for map in self.seed_location.iter() {
@@ -74,7 +181,41 @@ impl Almanac {
         cur
     }
 
+    // push whole ranges of seeds through each map at once,
+    // splitting them wherever they straddle a mapping
+    fn seed_ranges_to_locations(&self, seeds: Vec<Range<usize>>) -> Vec<Range<usize>> {
+        let mut cur = seeds;
+
+        for map in self.seed_location.iter() {
+            cur = map_ranges(cur, map);
+        }
+
+        cur
+    }
+
+    // seed -> location in one lookup
+    fn composed(&self) -> Piecewise {
+        self.seed_location
+            .iter()
+            .fold(Piecewise::identity(), |acc, map| acc.then(map))
+    }
+
+    // location -> seed in one lookup
+    fn inverted(&self) -> Result<Piecewise, String> {
+        self.composed().inverse()
+    }
+
+    // seeds line read as (start, len) pairs
+    fn seed_ranges(&self) -> Vec<Range<usize>> {
+        self.seeds
+            .chunks(2)
+            .map(|pair| pair[0]..pair[0] + pair[1])
+            .collect()
+    }
+
     // reverse
+    // only the tests use this now that part two maps ranges
+    #[cfg(test)]
     fn location_to_seed(&self, location: usize) -> Option<usize> {
         let mut cur = location;
 
@@ -120,6 +261,8 @@ fn x_to_y_map(n: usize, mapping: &Mapping) -> Option<usize> {
     Some(n + diff)
 }
 
+// only for location_to_seed
+#[cfg(test)]
 fn y_to_x_map(n: usize, mapping: &Mapping) -> Option<usize> {
     let (x, y, len) = *mapping;
 
@@ -138,11 +281,54 @@ fn y_to_x_map(n: usize, mapping: &Mapping) -> Option<usize> {
     Some(n + diff)
 }
 
+fn map_ranges(ranges: Vec<Range<usize>>, map: &[Mapping]) -> Vec<Range<usize>> {
+    let mut mapped = vec![];
+
+    for range in ranges {
+        // parts of this range that haven't hit a mapping yet
+        let mut pending = vec![range];
+
+        for (y, x, len) in map.iter() {
+            let (y, x, len) = (*y, *x, *len);
+            let mut missed = vec![];
+
+            for r in pending {
+                let start = r.start.max(x);
+                let end = r.end.min(x + len);
+
+                if start >= end {
+                    // no overlap at all
+                    missed.push(r);
+                    continue;
+                }
+
+                // the overlap moves, the rest keeps looking
+                mapped.push(start - x + y..end - x + y);
+
+                if r.start < start {
+                    missed.push(r.start..start);
+                }
+                if end < r.end {
+                    missed.push(end..r.end);
+                }
+            }
+
+            pending = missed;
+        }
+
+        // anything not in a mapping stays the same
+        mapped.extend(pending);
+    }
+
+    mapped
+}
+
 fn part_one(almanac: &Almanac) -> usize {
     let mut smallest = usize::MAX;
+    let composed = almanac.composed();
 
     for seed in almanac.seeds.iter() {
-        let location = almanac.seed_to_location(*seed);
+        let location = composed.get(*seed).expect("every seed has a location");
 
         if location < smallest {
             smallest = location;
@@ -153,16 +339,14 @@ fn part_one(almanac: &Almanac) -> usize {
 }
 
 fn part_two(almanac: &Almanac) -> usize {
-    let smallest = usize::MAX;
-
-    for l in 0usize.. {
-        // just go crazy
-        if almanac.location_to_seed(l).is_some() {
-            return l;
-        }
-    }
-
-    smallest
+    almanac
+        .seed_ranges_to_locations(almanac.seed_ranges())
+        .iter()
+        // a seed pair with no length has no seeds in it
+        .filter(|r| !r.is_empty())
+        .map(|r| r.start)
+        .min()
+        .unwrap_or(usize::MAX)
 }
 
 fn main() {
@@ -172,6 +356,15 @@ fn main() {
 
     let almanac = Almanac::new(contents.as_str());
 
+    if env::args().any(|arg| arg == "table") {
+        println!("seed -> location\n{}", almanac.composed());
+
+        match almanac.inverted() {
+            Ok(inverted) => println!("location -> seed\n{}", inverted),
+            Err(err) => println!("location -> seed: {}", err),
+        }
+    }
+
     if one {
         let now = Instant::now();
         let ans = part_one(&almanac);
@@ -227,6 +420,69 @@ mod tests {
         assert_eq!(almanac.location_to_seed(0), None);
     }
 
+    #[test]
+    fn test_map_ranges() {
+        // half in, half out
+        assert_eq!(map_ranges(vec![Range { start: 45, end: 55 }], &[(100, 50, 10)]), vec![100..105, 45..50]);
+        // nothing mapped
+        assert_eq!(map_ranges(vec![Range { start: 0, end: 10 }], &[(100, 50, 10)]), vec![0..10]);
+    }
+
+    #[test]
+    fn test_seed_ranges_to_locations() {
+        let almanac = Almanac::new(EXAMPLE);
+        let locations = almanac.seed_ranges_to_locations(vec![Range { start: 82, end: 83 }]);
+
+        assert_eq!(locations, vec![46..47]);
+
+        // same amount of seeds in, as locations out
+        let total: usize = almanac
+            .seed_ranges_to_locations(almanac.seed_ranges())
+            .iter()
+            .map(|r| r.len())
+            .sum();
+
+        assert_eq!(total, 14 + 13);
+    }
+
+    #[test]
+    fn test_composed() {
+        let almanac = Almanac::new(EXAMPLE);
+        let composed = almanac.composed();
+
+        // still covers everything, in order
+        assert_eq!(composed.pieces[0].1, 0);
+        assert!(composed.pieces.windows(2).all(|w| w[0].1 + w[0].2 == w[1].1));
+
+        for seed in 0..200 {
+            assert_eq!(composed.get(seed), Some(almanac.seed_to_location(seed)));
+        }
+    }
+
+    #[test]
+    fn test_inverted() {
+        let almanac = Almanac::new(EXAMPLE);
+        let inverted = almanac.inverted().unwrap();
+
+        assert_eq!(inverted.get(82), Some(79));
+        assert_eq!(inverted.get(86), Some(55));
+        assert_eq!(inverted.inverse(), Ok(almanac.composed()));
+
+        for seed in 0..200 {
+            let location = almanac.seed_to_location(seed);
+
+            assert_eq!(inverted.get(location), Some(seed));
+        }
+    }
+
+    #[test]
+    fn test_not_one_to_one() {
+        // 10..15 goes to 0..5, but 0..5 stays where it is too
+        let almanac = Almanac::new("seeds: 1\n\nseed-to-soil map:\n0 10 5");
+
+        assert_eq!(almanac.inverted().err(), Some("0..5 comes from more than one place".to_string()));
+    }
+
     #[test]
     fn test_part_two() {
         let almanac = Almanac::new(EXAMPLE);
@@ -234,4 +490,12 @@ mod tests {
 
         assert_eq!(ans, 46);
     }
+
+    #[test]
+    fn test_empty_seed_range() {
+        let contents = EXAMPLE.replace("seeds: 79 14 55 13", "seeds: 0 0 79 14 55 13");
+        let almanac = Almanac::new(&contents);
+
+        assert_eq!(part_two(&almanac), 46);
+    }
 }
@@ -46,8 +46,58 @@ impl Race {
 
         Race { time: lines[0], dist: lines[1] }
     }
+
+    // how far the boat goes if we hold for `hold` ms
+    fn dist_for_hold(&self, hold: u128) -> u128 {
+        hold * (self.time as u128 - hold)
+    }
+
+    // hold * (time - hold) > dist is a quadratic, so the winning holds are
+    // everything between its two roots:
+    // (time ± sqrt(time² - 4 * dist)) / 2
+    fn ways_to_win(&self) -> usize {
+        // u128 so time² doesn't overflow
+        let time = self.time as u128;
+        let dist = self.dist as u128;
+
+        // best we can do is hold for half the time
+        if time * time / 4 <= dist {
+            return 0;
+        }
+
+        let root = isqrt(time * time - 4 * dist);
+        let mut low = (time - root) / 2;
+
+        // the root is rounded, so nudge it onto the first winning hold
+        while low > 0 && self.dist_for_hold(low - 1) > dist {
+            low -= 1;
+        }
+        while self.dist_for_hold(low) <= dist {
+            low += 1;
+        }
+
+        // it's symmetric: the last winning hold is time - low
+        (time - 2 * low + 1) as usize
+    }
 }
 
+// f64 gets us close, then fix it up so it's exact
+fn isqrt(n: u128) -> u128 {
+    // anything bigger would overflow when squared
+    let mut root = ((n as f64).sqrt() as u128).min(u64::MAX as u128);
+
+    while root * root > n {
+        root -= 1;
+    }
+    while (root + 1).checked_mul(root + 1).is_some_and(|sq| sq <= n) {
+        root += 1;
+    }
+
+    root
+}
+
+// brute force; the tests check `ways_to_win` against this
+#[cfg(test)]
 fn get_dist_for_hold(time: usize) -> Vec<usize> {
     let mut ret = vec![];
 
@@ -60,17 +110,11 @@ fn get_dist_for_hold(time: usize) -> Vec<usize> {
 }
 
 fn part_one(races: Vec<Race>) -> usize {
-    races.iter().map(|r: &Race| {
-        get_dist_for_hold(r.time).iter().filter(|x: &&usize| {
-            **x > r.dist
-        }).count()
-    }).product()
+    races.iter().map(|r| r.ways_to_win()).product()
 }
 
 fn part_two(race: Race) -> usize {
-    get_dist_for_hold(race.time).iter().filter(|x| {
-        **x > race.dist
-    }).count()
+    race.ways_to_win()
 }
 
 fn main() {
@@ -115,6 +159,43 @@ mod tests {
         assert_eq!(get_dist_for_hold(7), [6, 10, 12, 12, 10, 6]);
     }
 
+    #[test]
+    fn test_isqrt() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_ways_to_win() {
+        // check against brute force
+        for time in 0..60 {
+            for dist in 0..(time * time / 4 + 2) {
+                let race = Race { time, dist };
+                let expected = get_dist_for_hold(time)
+                    .iter()
+                    .filter(|x| **x > dist)
+                    .count();
+
+                assert_eq!(race.ways_to_win(), expected, "{:?}", race);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ways_to_win_huge() {
+        let max = u64::MAX as usize;
+
+        // every hold but 0 and max
+        assert_eq!(Race { time: max, dist: 0 }.ways_to_win(), max - 1);
+        // holding for 1 ms (or max - 1) doesn't quite make it
+        assert_eq!(Race { time: max, dist: max }.ways_to_win(), max - 3);
+        // just the middle hold, then just the two middle holds
+        assert_eq!(Race { time: 1 << 32, dist: (1 << 62) - 1 }.ways_to_win(), 1);
+        assert_eq!(Race { time: (1 << 32) + 1, dist: (1 << 62) + (1 << 31) - 1 }.ways_to_win(), 2);
+    }
+
     #[test]
     fn test_part_one() {
         let races = Race::list(EXAMPLE);
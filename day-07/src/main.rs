@@ -1,142 +1,171 @@
-use std::{ time::Instant, fs, collections::HashMap, cmp::Ordering };
+use std::{ env, time::Instant, fs, cmp::Ordering };
 use lib::get_part;
 
-// reverse order, because that's how I can compare via FullHouse > Three
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum CamelType {
-    HighCard(Vec<u8>),
-    Pair(Vec<u8>),
-    TwoPair(Vec<u8>),
-    Three(Vec<u8>),
-    FullHouse(Vec<u8>),
-    Four(Vec<u8>),
-    Five(Vec<u8>),
+#[derive(Debug, Clone, PartialEq)]
+struct Category {
+    name: &'static str,
+    // the groups of matching cards it needs, biggest first; (e.g. full house is [3, 2])
+    groups: Vec<usize>,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd)]
-struct CamelHand {
-    hand: CamelType,
-    bid: usize,
+impl Category {
+    fn new(name: &'static str, groups: &[usize]) -> Self {
+        Self { name, groups: groups.to_vec() }
+    }
 }
 
-impl Ord for CamelHand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        // I can't believe I have to do this
-        match (&self.hand, &other.hand) {
-            | (CamelType::HighCard(a), CamelType::HighCard(b))
-            | (CamelType::Pair(a), CamelType::Pair(b))
-            | (CamelType::TwoPair(a), CamelType::TwoPair(b))
-            | (CamelType::Three(a), CamelType::Three(b))
-            | (CamelType::FullHouse(a), CamelType::FullHouse(b))
-            | (CamelType::Four(a), CamelType::Four(b))
-            | (CamelType::Five(a), CamelType::Five(b)) => {
-                // check the cards (vec<u8> has `.cmp()`)
-                return a.cmp(&b);
-            }
-            _ => {
-                if self.hand < other.hand {
-                    return Ordering::Less;
-                }
-                
-                if self.hand > other.hand {
-                    return Ordering::Greater;
-                }
-                return Ordering::Equal;
-            }
-        }
-    }
+// everything that changes between camel card games
+#[derive(Debug, Clone)]
+struct Rules {
+    // weakest to strongest
+    order: Vec<char>,
+    // can be counted as whatever card makes the best category
+    wild: Vec<char>,
+    hand_size: usize,
+    // weakest to strongest
+    categories: Vec<Category>,
 }
 
-impl CamelHand {
-    fn new(data: (&str, &str), part: u8) -> Self {
-        CamelHand {
-            hand: Self::hand_to_category(data.0, part),
-            bid: data.1.parse().unwrap(),
+impl Rules {
+    fn part_one() -> Self {
+        Self {
+            order: "23456789TJQKA".chars().collect(),
+            wild: vec![],
+            hand_size: 5,
+            categories: vec![
+                Category::new("high card", &[]),
+                Category::new("one pair", &[2]),
+                Category::new("two pair", &[2, 2]),
+                Category::new("three of a kind", &[3]),
+                Category::new("full house", &[3, 2]),
+                Category::new("four of a kind", &[4]),
+                Category::new("five of a kind", &[5]),
+            ],
         }
     }
 
-    fn hand_to_category(hand: &str, part: u8) -> CamelType {
-        Self::categorize_digits(Self::hand_to_digits(hand, part), part)
+    // jokers are wild, but the weakest on their own
+    fn part_two() -> Self {
+        Self {
+            order: "J23456789TQKA".chars().collect(),
+            wild: vec!['J'],
+            ..Self::part_one()
+        }
     }
 
-    fn categorize_digits(hand: Vec<u8>, part: u8) -> CamelType {
-        let mut counts: HashMap<&u8, i32> = HashMap::new();
+    fn rank(&self, card: char) -> Option<usize> {
+        self.order.iter().position(|c| *c == card)
+    }
 
-        for card in hand.iter() {
-            if let Some(x) = counts.get_mut(card) {
-                *x += 1;
+    // strongest category the hand can make, with wild cards wherever they help
+    fn category(&self, cards: &str) -> usize {
+        let mut counts: Vec<usize> = vec![];
+        let mut wild = 0;
+        let mut seen: Vec<char> = vec![];
+
+        for card in cards.chars() {
+            if self.wild.contains(&card) {
+                wild += 1;
+            } else if let Some(i) = seen.iter().position(|c| *c == card) {
+                counts[i] += 1;
             } else {
-                counts.insert(card, 1);
+                seen.push(card);
+                counts.push(1);
             }
         }
 
-        // need to count 'J' in part 2, before categorizing
-        if part == 2 {
-            Self::alter_counts(&mut counts);
-        }
+        counts.sort_by(|a, b| b.cmp(a));
 
-        // deduce the hand from the count groups
-        match counts.len() {
-            1 => { CamelType::Five(hand) }
-            2 => {
-                if counts.values().any(|x| *x == 2) {
-                    return CamelType::FullHouse(hand);
-                }
-                CamelType::Four(hand)
-            }
-            3 => {
-                if counts.values().any(|x| *x == 2) {
-                    return CamelType::TwoPair(hand);
-                }
-                CamelType::Three(hand)
-            }
-            4 => { CamelType::Pair(hand) }
-            _ => { CamelType::HighCard(hand) }
-        }
+        let fits = |category: &Category| {
+            // biggest groups go to the biggest counts; wilds make up the difference
+            let needed: usize = category.groups
+                .iter()
+                .enumerate()
+                .map(|(i, group)| group.saturating_sub(*counts.get(i).unwrap_or(&0)))
+                .sum();
+
+            needed <= wild
+        };
+
+        self.categories
+            .iter()
+            .rposition(fits)
+            .unwrap_or(0)
     }
 
-    fn alter_counts(counts: &mut HashMap<&u8, i32>) {
-        if let Some(joker) = counts.get(&1) {
-            // found a joker
-            // these are crazy types
-            let mut max: (&&u8, &i32) = (&&0u8, &0i32);
-            for count in counts.iter() {
-                if count.0 == &&1u8 {
-                    // don't count jokers 
-                    continue;
-                }
-                if count.1 > &max.1 {
-                    max = count;
-                }
-            }
+    fn hand(&self, line: &str) -> Result<CamelHand, String> {
+        let (cards, bid) = line.split_once(' ').ok_or(format!("no bid in {}", line))?;
 
-            // give all jokers to the max
-            counts.insert(max.0, max.1 + joker);
-            counts.remove(&1);
+        if cards.chars().count() != self.hand_size {
+            return Err(format!("{} isn't {} cards", cards, self.hand_size));
         }
-    }
 
-    fn hand_to_digits(hand: &str, part: u8) -> Vec<u8> {
-        hand.chars()
-            .map(|c| {
-                match c {
-                    'A' => { 14 }
-                    'K' => { 13 }
-                    'Q' => { 12 }
-                    'J' => { if part == 1 { 11 } else { 1 } }
-                    'T' => { 10 }
-                    n => n.to_digit(10).unwrap() as u8,
-                }
-            })
-            .collect()
+        let ranks = cards
+            .chars()
+            .map(|c| self.rank(c).ok_or(format!("{} isn't a card", c)))
+            .collect::<Result<Vec<usize>, String>>()?;
+
+        Ok(CamelHand {
+            category: self.category(cards),
+            ranks,
+            cards: cards.to_string(),
+            bid: bid.parse().map_err(|_| format!("{} isn't a bid", bid))?,
+        })
     }
 
-    fn hands(contents: &str, part: u8) -> Vec<Self> {
+    fn hands(&self, contents: &str) -> Vec<CamelHand> {
         contents
             .lines()
-            .map(|l| { Self::new(l.split_once(" ").unwrap(), part) })
+            .map(|l| self.hand(l).unwrap())
             .collect()
     }
+
+    // why a comes out ahead of (or behind) b
+    fn explain(&self, a: &CamelHand, b: &CamelHand) -> String {
+        let name = |hand: &CamelHand| {
+            let name = self.categories[hand.category].name;
+            let wild = hand.cards.chars().filter(|c| self.wild.contains(c)).count();
+
+            match wild {
+                0 => name.to_string(),
+                _ => format!("{} (with {} wild)", name, wild),
+            }
+        };
+
+        // not by bid, so the same cards tie
+        let verb = match (a.category, &a.ranks).cmp(&(b.category, &b.ranks)) {
+            Ordering::Greater => "beats",
+            Ordering::Less => "loses to",
+            Ordering::Equal => return format!("{} ties {}: both are {}, with the same cards", a.cards, b.cards, name(a)),
+        };
+
+        if a.category != b.category {
+            return format!("{} {} {}: {} {} {}", a.cards, verb, b.cards, name(a), verb, name(b));
+        }
+
+        let i = a.ranks
+            .iter()
+            .zip(b.ranks.iter())
+            .position(|(x, y)| x != y)
+            .expect("a different card somewhere");
+
+        format!(
+            "{} {} {}: both are {}, and card {} {} {} {}",
+            a.cards, verb, b.cards, self.categories[a.category].name,
+            i + 1, self.order[a.ranks[i]], verb, self.order[b.ranks[i]]
+        )
+    }
+}
+
+// compares by category first, then card by card
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct CamelHand {
+    // index into the rules' categories
+    category: usize,
+    // index into the rules' order, for each card
+    ranks: Vec<usize>,
+    cards: String,
+    bid: usize,
 }
 
 fn part_one(hands: &mut Vec<CamelHand>) -> usize {
@@ -153,7 +182,7 @@ fn part_one(hands: &mut Vec<CamelHand>) -> usize {
 }
 
 fn part_two(hands: &mut Vec<CamelHand>) -> usize {
-    // this feels like a code smell
+    // same game, different rules
     part_one(hands)
 }
 
@@ -162,20 +191,30 @@ fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string("./src/input.txt").unwrap();
 
-    
+    // cargo run -- explain: why each hand ranks above the one before it
+    if env::args().any(|arg| arg == "explain") {
+        let rules = Rules::part_two();
+        let mut hands = rules.hands(contents.as_str());
+
+        hands.sort();
+
+        for pair in hands.windows(2) {
+            println!("{}", rules.explain(&pair[1], &pair[0]));
+        }
+
+        return;
+    }
+
     if one {
         let now = Instant::now();
-    
-        let mut hands = CamelHand::hands(contents.as_str(), 1);
-        
-        let ans = part_one(&mut hands);    
-        
+        let mut hands = Rules::part_one().hands(contents.as_str());
+        let ans = part_one(&mut hands);
         println!("Part one: {:?} {:?}", ans, now.elapsed());
     }
-    
+
     if two {
         let now = Instant::now();
-        let mut hands = CamelHand::hands(contents.as_str(), 2);
+        let mut hands = Rules::part_two().hands(contents.as_str());
         let ans = part_two(&mut hands);
         println!("Part two: {:?} {:?}", ans, now.elapsed());
     }
@@ -189,52 +228,48 @@ mod tests {
 
     const EXAMPLE: &str = include_str!("./example.txt");
 
+    fn hand(rules: &Rules, cards: &str) -> CamelHand {
+        rules.hand(&format!("{} 0", cards)).unwrap()
+    }
+
+    fn category(rules: &Rules, cards: &str) -> &'static str {
+        rules.categories[rules.category(cards)].name
+    }
+
     #[test]
-    fn test_hand_digits() {
-        assert_eq!(CamelHand::hand_to_digits("32T3K", 1), [3, 2, 10, 3, 13]);
+    fn test_hand_ranks() {
+        assert_eq!(hand(&Rules::part_one(), "32T3K").ranks, [1, 0, 8, 1, 11]);
+        assert_eq!(hand(&Rules::part_two(), "32J3K").ranks, [2, 1, 0, 2, 11]);
     }
 
     #[test]
     fn test_hand_category() {
-        assert!(
-            matches!(CamelHand::hand_to_category("32T3K", 1), CamelType::Pair(_))
-        );
-        assert!(
-            matches!(CamelHand::hand_to_category("11111", 1), CamelType::Five(_))
-        );
-        assert!(
-            matches!(CamelHand::hand_to_category("21111", 1), CamelType::Four(_))
-        );
-        assert!(
-            matches!(CamelHand::hand_to_category("23111", 1), CamelType::Three(_))
-        );
-        assert!(
-            matches!(
-                CamelHand::hand_to_category("22111", 1),
-                CamelType::FullHouse(_)
-            )
-        );
-        assert!(
-            matches!(
-                CamelHand::hand_to_category("32211", 1),
-                CamelType::TwoPair(_)
-            )
-        );
-        assert!(
-            matches!(CamelHand::hand_to_category("23411", 1), CamelType::Pair(_))
-        );
-        assert!(
-            matches!(
-                CamelHand::hand_to_category("12345", 1),
-                CamelType::HighCard(_)
-            )
-        );
+        let rules = Rules::part_one();
+
+        assert_eq!(category(&rules, "32T3K"), "one pair");
+        assert_eq!(category(&rules, "22222"), "five of a kind");
+        assert_eq!(category(&rules, "32222"), "four of a kind");
+        assert_eq!(category(&rules, "34222"), "three of a kind");
+        assert_eq!(category(&rules, "33222"), "full house");
+        assert_eq!(category(&rules, "43322"), "two pair");
+        assert_eq!(category(&rules, "34522"), "one pair");
+        assert_eq!(category(&rules, "23456"), "high card");
+    }
+
+    #[test]
+    fn test_bad_hands() {
+        let rules = Rules::part_one();
+
+        assert_eq!(rules.hand("2345 1").err(), Some("2345 isn't 5 cards".to_string()));
+        assert_eq!(rules.hand("1234A 1").err(), Some("1 isn't a card".to_string()));
+        assert_eq!(rules.hand("23456").err(), Some("no bid in 23456".to_string()));
     }
 
     #[test]
     fn test_cmp() {
-        let hand1: CamelHand = CamelHand::new(("KK111", "0"), 1);
-        let hand2: CamelHand = CamelHand::new(("KKK21", "0"), 1);
+        let rules = Rules::part_one();
+        let hand1 = hand(&rules, "KK222");
+        let hand2 = hand(&rules, "KKK32");
 
         // not sure why we have so many gt functions
         assert!(hand1 > hand2);
@@ -244,10 +279,11 @@ mod tests {
 
     #[test]
     fn test_sort() {
-        let hand1 = CamelHand::new(("12345", "0"), 1);
-        let hand2 = CamelHand::new(("11234", "0"), 1);
-        let hand3 = CamelHand::new(("11112", "0"), 1);
-        let hand4 = CamelHand::new(("11111", "0"), 1);
+        let rules = Rules::part_one();
+        let hand1 = hand(&rules, "23456");
+        let hand2 = hand(&rules, "22345");
+        let hand3 = hand(&rules, "22223");
+        let hand4 = hand(&rules, "22222");
 
         let mut hands = [&hand4, &hand2, &hand1, &hand3];
 
@@ -258,8 +294,9 @@ mod tests {
 
     #[test]
     fn test_first_best() {
-        let hand1 = CamelHand::new(("11633", "0"), 1);
-        let hand2 = CamelHand::new(("11522", "0"), 1);
+        let rules = Rules::part_one();
+        let hand1 = hand(&rules, "22744");
+        let hand2 = hand(&rules, "22633");
 
         let mut hands = [&hand1, &hand2];
 
@@ -270,26 +307,82 @@ mod tests {
 
     #[test]
     fn test_part_one() {
-        let mut hands = CamelHand::hands(EXAMPLE, 1);
+        let mut hands = Rules::part_one().hands(EXAMPLE);
 
         let ans = part_one(&mut hands);
 
         assert_eq!(ans, 6440);
     }
-    
+
+    #[test]
+    fn test_wild() {
+        let rules = Rules::part_two();
+
+        assert_eq!(category(&rules, "33J22"), "full house");
+        assert_eq!(category(&rules, "KTJJT"), "four of a kind");
+        assert_eq!(category(&rules, "JJJJJ"), "five of a kind");
+        assert_eq!(category(&rules, "2345J"), "one pair");
+    }
+
+    #[test]
+    fn test_tens_wild() {
+        let rules = Rules { wild: vec!['T'], ..Rules::part_one() };
+
+        assert_eq!(category(&rules, "32T3K"), "three of a kind");
+        assert_eq!(category(&rules, "KTJJT"), "four of a kind");
+        // jokers aren't wild any more
+        assert_eq!(category(&rules, "33J22"), "two pair");
+    }
+
+    #[test]
+    fn test_other_games() {
+        // three card hands, with only pairs and trips
+        let rules = Rules {
+            hand_size: 3,
+            categories: vec![
+                Category::new("nothing", &[]),
+                Category::new("pair", &[2]),
+                Category::new("trips", &[3]),
+            ],
+            ..Rules::part_two()
+        };
+
+        assert_eq!(category(&rules, "AKQ"), "nothing");
+        assert_eq!(category(&rules, "AJQ"), "pair");
+        assert_eq!(category(&rules, "JJ2"), "trips");
+        assert!(rules.hand("AAAA 1").is_err());
+    }
+
     #[test]
-    fn test_alter_counts() {
-        assert!(
-            matches!(CamelHand::hand_to_category("33J22", 2), CamelType::FullHouse(_))
+    fn test_explain() {
+        let rules = Rules::part_two();
+        let hands = rules.hands(EXAMPLE);
+
+        assert_eq!(
+            rules.explain(&hands[0], &hands[2]),
+            "32T3K loses to KK677: one pair loses to two pair"
+        );
+        assert_eq!(
+            rules.explain(&hands[3], &hands[4]),
+            "KTJJT beats QQQJA: both are four of a kind, and card 1 K beats Q"
+        );
+        assert_eq!(
+            rules.explain(&hands[1], &hands[2]),
+            "T55J5 beats KK677: four of a kind (with 1 wild) beats two pair"
+        );
+        assert_eq!(
+            rules.explain(&hands[2], &hands[2]),
+            "KK677 ties KK677: both are two pair, with the same cards"
         );
-        assert!(
-            matches!(CamelHand::hand_to_category("KTJJT", 2), CamelType::Four(_))
+        assert_eq!(
+            rules.explain(&rules.hand("KK677 2").unwrap(), &rules.hand("KK677 1").unwrap()),
+            "KK677 ties KK677: both are two pair, with the same cards"
         );
     }
 
     #[test]
     fn test_part_two() {
-        let mut hands = CamelHand::hands(EXAMPLE, 2);
+        let mut hands = Rules::part_two().hands(EXAMPLE);
 
         let ans = part_two(&mut hands);
 
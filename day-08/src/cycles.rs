@@ -0,0 +1,255 @@
+// where each ghost ends up looping, so we don't have to trust that lcm works
+
+use std::collections::HashMap;
+use lib::crt;
+use crate::{ Network, Dir, Query };
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Cycle {
+    // steps before the ghost starts going around
+    pub(crate) prefix: usize,
+    // steps to go around once
+    pub(crate) period: usize,
+    // steps on a Z before the loop starts
+    pub(crate) early: Vec<usize>,
+    // steps on a Z after prefix, the first time around
+    pub(crate) offsets: Vec<usize>,
+}
+
+impl Cycle {
+    fn is_hit(&self, step: usize) -> bool {
+        if step < self.prefix {
+            return self.early.contains(&step);
+        }
+
+        self.offsets.contains(&((step - self.prefix) % self.period))
+    }
+}
+
+impl Network<'_> {
+    // a ghost is only somewhere it's been before if it's at the same instruction too
+    pub(crate) fn get_cycle(&self, key: &str, ends: &Query) -> Cycle {
+        let len = self.instructions.len();
+        let mut seen: HashMap<(&str, usize), usize> = HashMap::new();
+        let mut hits = vec![];
+        let mut key = key;
+        let mut step = 0;
+
+        let prefix = loop {
+            if let Some(first) = seen.insert((key, step % len), step) {
+                break first;
+            }
+
+            if ends.matches(key) {
+                hits.push(step);
+            }
+
+            let (l, r) = self.elements.get(key).unwrap();
+
+            key = match self.instructions[step % len] {
+                Dir::L => { l }
+                Dir::R => { r }
+            };
+
+            step += 1;
+        };
+
+        let (early, offsets): (Vec<usize>, Vec<usize>) = hits
+            .iter()
+            .partition(|hit| **hit < prefix);
+
+        Cycle {
+            prefix,
+            period: step - prefix,
+            // step 0 is before anyone's moved, so it never counts on its own
+            early: early.into_iter().filter(|hit| *hit > 0).collect(),
+            // but it does when the loop starts there, since it comes round again
+            offsets: offsets.iter().map(|hit| hit - prefix).collect(),
+        }
+    }
+
+    // the first step where every ghost is on an end, if there is one
+    pub(crate) fn earliest_common_step(&self, starts: &Query, ends: &Query) -> Option<usize> {
+        let cycles: Vec<Cycle> = self.keys_matching(starts)
+            .iter()
+            .map(|key| self.get_cycle(key, ends))
+            .collect();
+
+        earliest(&cycles)
+    }
+}
+
+pub(crate) fn earliest(cycles: &[Cycle]) -> Option<usize> {
+    let longest = cycles.iter().max_by_key(|c| c.prefix)?;
+
+    // before everyone's looping, just check; the longest prefix saw every hit there
+    for step in longest.early.iter() {
+        if cycles.iter().all(|c| c.is_hit(*step)) {
+            return Some(*step);
+        }
+    }
+
+    // after that, it's every combination of offsets, as (residue, modulus)
+    let mut options: Vec<(i128, i128)> = vec![(0, 1)];
+
+    for cycle in cycles {
+        let period = cycle.period as i128;
+        let mut next = vec![];
+
+        for (residue, modulus) in options.iter() {
+            for offset in cycle.offsets.iter() {
+                let hit = ((cycle.prefix + offset) as i128) % period;
+
+                if let Some(combined) = crt((*residue, *modulus), (hit, period)) {
+                    if !next.contains(&combined) {
+                        next.push(combined);
+                    }
+                }
+            }
+        }
+
+        options = next;
+    }
+
+    // the first time each one comes up once everyone is looping (and we've moved)
+    let from = longest.prefix.max(1) as i128;
+
+    options
+        .iter()
+        .map(|(residue, modulus)| {
+            if *residue >= from {
+                return *residue;
+            }
+
+            residue + (from - residue + modulus - 1) / modulus * modulus
+        })
+        .min()
+        .map(|step| step as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: Query = Query::Glob("*A");
+    const Z: Query = Query::Glob("*Z");
+
+    fn brute_force(network: &Network, starts: &Query, limit: usize) -> Option<usize> {
+        let mut keys = network.keys_matching(starts);
+        let len = network.instructions.len();
+
+        for step in 0..limit {
+            keys = keys
+                .iter()
+                .map(|key| {
+                    let (l, r) = network.elements.get(key).unwrap();
+
+                    match network.instructions[step % len] {
+                        Dir::L => *l,
+                        Dir::R => *r,
+                    }
+                })
+                .collect();
+
+            if keys.iter().all(|key| key.ends_with('Z')) {
+                return Some(step + 1);
+            }
+        }
+
+        None
+    }
+
+    #[test]
+    fn test_cycle() {
+        let network = Network::new("LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+XXX = (XXX, XXX)");
+
+        assert_eq!(network.get_cycle("11A", &Z), Cycle { prefix: 1, period: 2, early: vec![], offsets: vec![1] });
+    }
+
+    #[test]
+    fn test_offsets_not_at_the_end() {
+        // 11A loops from the start, hitting Z at 2; 22A loops after one step, hitting Z at 3
+        let network = Network::new("L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11A, 11A)
+22A = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22D, 22D)
+22D = (22B, 22B)");
+
+        assert_eq!(network.get_cycle("22A", &Z), Cycle { prefix: 1, period: 4, early: vec![], offsets: vec![2] });
+        assert_eq!(network.earliest_common_step(&A, &Z), Some(11));
+        assert_eq!(brute_force(&network, &A, 100), Some(11));
+    }
+
+    #[test]
+    fn test_many_hits() {
+        // 11A is on a Z every other step
+        let network = Network::new("L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (12Z, 12Z)
+12Z = (11A, 11A)
+22A = (22B, 22B)
+22B = (22Z, 22Z)
+22Z = (22A, 22A)");
+
+        assert_eq!(network.get_cycle("11A", &Z).offsets, vec![1, 3]);
+        assert_eq!(network.earliest_common_step(&A, &Z), Some(5));
+        assert_eq!(brute_force(&network, &A, 100), Some(5));
+    }
+
+    #[test]
+    fn test_before_looping() {
+        // 11A only hits Z once, on the way into the loop
+        let network = Network::new("L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22Z, 22Z)");
+
+        assert_eq!(network.get_cycle("11A", &Z), Cycle { prefix: 2, period: 1, early: vec![1], offsets: vec![] });
+        assert_eq!(network.earliest_common_step(&A, &Z), Some(1));
+    }
+
+    #[test]
+    fn test_on_an_end_from_the_start() {
+        // AZ loops from step 0, so its hit there comes round every step
+        let network = Network::new("L
+
+AZ = (AZ, AZ)
+BA = (BZ, BZ)
+BZ = (BZ, BZ)");
+        let all = Query::Glob("*");
+
+        assert_eq!(network.get_cycle("AZ", &Z), Cycle { prefix: 0, period: 1, early: vec![], offsets: vec![0] });
+        assert_eq!(network.earliest_common_step(&all, &Z), Some(1));
+        assert_eq!(brute_force(&network, &all, 100), Some(1));
+    }
+
+    #[test]
+    fn test_never() {
+        // one is only on Z at odd steps, the other at even
+        let network = Network::new("L
+
+11A = (11Z, 11Z)
+11Z = (11A, 11A)
+22A = (22B, 22B)
+22B = (22Z, 22Z)
+22Z = (22B, 22B)");
+
+        assert_eq!(network.earliest_common_step(&A, &Z), None);
+        assert_eq!(brute_force(&network, &A, 100), None);
+    }
+}
@@ -1,6 +1,11 @@
-use std::{ time::Instant, fs, collections::HashMap };
+use std::{ env, time::Instant, fs, collections::HashMap };
 use lib::get_part;
 
+mod cycles;
+mod walk;
+
+use walk::Query;
+
 #[derive(Debug, PartialEq)]
 enum Dir {
     L,
@@ -31,62 +36,33 @@ impl Network<'_> {
 
         let mut elements = HashMap::new();
 
-        for line in lines.skip(1) {
-            let key = &line[..3];
-            let left = &line[7..10];
-            let right = &line[12..15];
+        // names can be any length
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let (key, next) = line.split_once('=').expect("key = (left, right)");
+            let (left, right) = next
+                .trim()
+                .trim_matches(|c| c == '(' || c == ')')
+                .split_once(',')
+                .expect("(left, right)");
 
-            elements.insert(key, (left, right));
+            elements.insert(key.trim(), (left.trim(), right.trim()));
         }
 
         Network { instructions, elements }
     }
-    fn get_start_keys(&self) -> Vec<&str> {
-        self.elements
-            .iter()
-            .filter_map(|elem| {
-                if elem.0.chars().nth(2).unwrap() == 'A' {
-                    Some(*elem.0)
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<&str>>()
-    }
-    fn count_path(&self, key: &str) -> usize {
-        // iterate instructions, return # of iterations
-        let mut key = key;
-        let mut i = 0;
-        let len = self.instructions.len();
-
-        loop {
-            let (l, r) = self.elements.get(key).unwrap();
-
-            key = match self.instructions[i % len] {
-                Dir::L => { l }
-                Dir::R => { r }
-            };
-
-            i += 1;
-
-            if key.chars().nth(2).unwrap() == 'Z' {
-                return i;
-            }
-        }
-    }
 }
 
 fn part_one(network: &Network) -> usize {
-    network.count_path("AAA")
+    network
+        .walk("AAA", &Query::Glob("ZZZ"))
+        .expect("AAA to get to ZZZ")
+        .steps
 }
 
 fn part_two(network: &Network) -> usize {
-    let keys = network.get_start_keys();
-
-    keys.iter()
-        .map(|x| network.count_path(x))
-        .reduce(lcm)
-        .unwrap()
+    network
+        .earliest_common_step(&Query::Glob("*A"), &Query::Glob("*Z"))
+        .expect("ghosts to all get to Z at some point")
 }
 
 fn main() {
@@ -95,6 +71,22 @@ fn main() {
     let contents = fs::read_to_string("./src/input.txt").unwrap();
 
     let network = Network::new(contents.as_str());
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // cargo run -- walk "??A" "*Z"
+    if args.first().is_some_and(|arg| arg == "walk") {
+        let starts = Query::Glob(args.get(1).map_or("AAA", |s| s.as_str()));
+        let ends = Query::Glob(args.get(2).map_or("ZZZ", |s| s.as_str()));
+
+        for (start, walk) in network.walks(&starts, &ends) {
+            match walk {
+                Some(walk) => println!("{}: {} steps, {}", start, walk.steps, walk.path.join(" -> ")),
+                None => println!("{}: never gets there", start),
+            }
+        }
+
+        return;
+    }
 
     if one {
         let now = Instant::now();
@@ -111,22 +103,6 @@ fn main() {
     println!("Time: {:?}", start.elapsed())
 }
 
-fn lcm(a: usize, b: usize) -> usize {
-    if a == 0 {
-        return b;
-    }
-
-    (a * b) / gcd(a, b)
-}
-
-fn gcd(a: usize, b: usize) -> usize {
-    if a == 0 {
-        return b;
-    }
-
-    gcd(b % a, a)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,7 +147,7 @@ XXX = (XXX, XXX)";
     fn test_start_keys() {
         let network = Network::new(EXAMPLE_2);
 
-        assert_eq!(network.get_start_keys().len(), 2);
+        assert_eq!(network.keys_matching(&Query::Glob("*A")), vec!["11A", "22A"]);
     }
 
     #[test]
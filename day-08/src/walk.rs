@@ -0,0 +1,155 @@
+// walking the network from anywhere to anywhere, not just A's to Z's
+
+use std::{ collections::HashSet, fmt };
+use crate::{ Network, Dir };
+
+// which nodes to start or stop at
+pub(crate) enum Query<'q> {
+    // * for any run of characters, ? for any one
+    Glob(&'q str),
+    #[allow(dead_code)]
+    Predicate(&'q dyn Fn(&str) -> bool),
+}
+
+impl Query<'_> {
+    pub(crate) fn matches(&self, key: &str) -> bool {
+        match self {
+            Query::Glob(pattern) => {
+                let pattern: Vec<char> = pattern.chars().collect();
+                let key: Vec<char> = key.chars().collect();
+
+                glob(&pattern, &key)
+            }
+            Query::Predicate(f) => f(key),
+        }
+    }
+}
+
+impl fmt::Debug for Query<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Query::Glob(pattern) => write!(f, "Glob({:?})", pattern),
+            Query::Predicate(_) => write!(f, "Predicate"),
+        }
+    }
+}
+
+fn glob(pattern: &[char], key: &[char]) -> bool {
+    match (pattern.first(), key.first()) {
+        (None, None) => true,
+        // either skip the star, or let it eat one more character
+        (Some('*'), _) => glob(&pattern[1..], key) || (!key.is_empty() && glob(pattern, &key[1..])),
+        (Some('?'), Some(_)) => glob(&pattern[1..], &key[1..]),
+        (Some(p), Some(k)) => p == k && glob(&pattern[1..], &key[1..]),
+        _ => false,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Walk<'a> {
+    pub(crate) steps: usize,
+    // every node, from the start to the end
+    pub(crate) path: Vec<&'a str>,
+}
+
+impl<'a> Network<'a> {
+    // sorted, so walks come back in the same order every time
+    pub(crate) fn keys_matching(&self, query: &Query) -> Vec<&'a str> {
+        let mut keys: Vec<&str> = self.elements
+            .keys()
+            .filter(|key| query.matches(key))
+            .copied()
+            .collect();
+
+        keys.sort();
+
+        keys
+    }
+
+    // None if it goes around forever without getting to an end
+    pub(crate) fn walk(&self, start: &'a str, ends: &Query) -> Option<Walk<'a>> {
+        let len = self.instructions.len();
+        let mut seen = HashSet::new();
+        let mut path = vec![start];
+        let mut key = start;
+
+        // always take at least one step
+        while seen.insert((key, (path.len() - 1) % len)) {
+            let (l, r) = self.elements.get(key)?;
+
+            key = match self.instructions[(path.len() - 1) % len] {
+                Dir::L => { l }
+                Dir::R => { r }
+            };
+
+            path.push(key);
+
+            if ends.matches(key) {
+                return Some(Walk { steps: path.len() - 1, path });
+            }
+        }
+
+        None
+    }
+
+    // a walk for each start, in order
+    pub(crate) fn walks(&self, starts: &Query, ends: &Query) -> Vec<(&'a str, Option<Walk<'a>>)> {
+        self.keys_matching(starts)
+            .into_iter()
+            .map(|start| (start, self.walk(start, ends)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("./example.txt");
+
+    #[test]
+    fn test_glob() {
+        assert!(Query::Glob("*Z").matches("ZZZ"));
+        assert!(Query::Glob("*Z").matches("Z"));
+        assert!(Query::Glob("??A").matches("11A"));
+        assert!(!Query::Glob("??A").matches("1A"));
+        assert!(Query::Glob("start*end").matches("start-middle-end"));
+        assert!(!Query::Glob("start*end").matches("start-middle"));
+        assert!(Query::Glob("*").matches(""));
+    }
+
+    #[test]
+    fn test_walk() {
+        let network = Network::new(EXAMPLE);
+        let walk = network.walk("AAA", &Query::Glob("ZZZ")).unwrap();
+
+        assert_eq!(walk.steps, 6);
+        assert_eq!(walk.path, vec!["AAA", "BBB", "AAA", "BBB", "AAA", "BBB", "ZZZ"]);
+    }
+
+    #[test]
+    fn test_long_names() {
+        let network = Network::new("RL
+
+start = (start, middle)
+middle = (finish, x)
+x = (x, x)
+finish = (finish, finish)
+");
+        let is_finish = |key: &str| key.starts_with('f');
+        let walks = network.walks(&Query::Glob("st*"), &Query::Predicate(&is_finish));
+
+        assert_eq!(walks, vec![
+            ("start", Some(Walk { steps: 2, path: vec!["start", "middle", "finish"] })),
+        ]);
+    }
+
+    #[test]
+    fn test_never_ends() {
+        let network = Network::new(EXAMPLE);
+
+        assert_eq!(network.walk("AAA", &Query::Glob("CCC")), None);
+        // not a node at all
+        assert_eq!(network.walk("CCC", &Query::Glob("ZZZ")), None);
+    }
+}
@@ -1,32 +1,66 @@
 use std::{ time::Instant, fs, vec };
 use lib::get_part;
 
-fn get_extrapolation(history: Vec<isize>) -> isize {
-    let mut diffs = vec![history];
-    loop {
-        let cur = &diffs[diffs.len() - 1];
-        let mut next: Vec<isize> = vec![];
-        // rev'ing twice to skip last
-        for (i, v) in cur.iter().rev().skip(1).rev().enumerate() {
-            let diff = cur[i + 1] - v;
-
-            next.push(diff);
+// a history as the polynomial that makes it, in newton's forward-difference form
+#[derive(Debug, PartialEq)]
+struct Polynomial {
+    // the first value of each row of differences, down to the last non-zero row
+    firsts: Vec<i128>,
+}
+
+impl Polynomial {
+    // errors if the differences never get to all zeros before running out
+    fn new(history: &[isize]) -> Result<Self, String> {
+        let mut cur: Vec<i128> = history.iter().map(|x| *x as i128).collect();
+        let mut firsts = vec![];
+
+        while !cur.iter().all(|x| *x == 0) {
+            // one value left, and it's not zero, so there's nothing to check it against
+            if cur.len() < 2 {
+                return Err(format!("history of {} isn't a polynomial", history.len()));
+            }
+
+            firsts.push(cur[0]);
+            cur = cur.windows(2).map(|w| w[1] - w[0]).collect();
         }
 
-        if next.iter().all(|x| x == &0) {
-            break;
+        if cur.is_empty() {
+            return Err("empty history".to_string());
         }
 
-        diffs.push(next);
+        Ok(Self { firsts })
+    }
+
+    // all zeros is degree 0, same as any other constant
+    #[allow(dead_code)]
+    fn degree(&self) -> usize {
+        self.firsts.len().max(1) - 1
     }
-    // now get the last value of all diffs
-    let mut prediction = 0;
 
-    for diff in diffs.iter().rev() {
-        prediction += diff.last().expect("is this what you like?");
+    // index 0 is the first value in the history; None if it's too big
+    fn at(&self, n: isize) -> Option<isize> {
+        let n = n as i128;
+        let mut value: i128 = 0;
+        // n choose k, which works for negative n too
+        let mut choose: i128 = 1;
+
+        for (k, first) in self.firsts.iter().enumerate() {
+            let k = k as i128;
+
+            value = value.checked_add(choose.checked_mul(*first)?)?;
+            // always divides evenly
+            choose = choose.checked_mul(n - k)? / (k + 1);
+        }
+
+        value.try_into().ok()
     }
+}
 
-    prediction
+fn get_extrapolation(history: Vec<isize>) -> isize {
+    Polynomial::new(&history)
+        .expect("a polynomial")
+        .at(history.len() as isize)
+        .expect("a prediction that fits")
 }
 
 fn parse(contents: &str) -> Vec<Vec<isize>> {
@@ -50,14 +84,13 @@ fn part_one(histories: &Vec<Vec<isize>>) -> isize {
 fn part_two(histories: &Vec<Vec<isize>>) -> isize {
     histories
         .iter()
-        .map(|x|
-            get_extrapolation({
-                let mut v = x.to_vec();
-                // reverse to get left-side instead
-                v.reverse();
-                v
-            })
-        )
+        .map(|x| {
+            // the one before the start
+            Polynomial::new(x)
+                .expect("a polynomial")
+                .at(-1)
+                .expect("a prediction that fits")
+        })
         .sum()
 }
 
@@ -94,6 +127,48 @@ mod tests {
         assert_eq!(get_extrapolation(vec![0, 3, 6, 9, 12, 15]), 18);
     }
 
+    #[test]
+    fn test_polynomial() {
+        let histories = parse(EXAMPLE);
+        let degrees: Vec<usize> = histories
+            .iter()
+            .map(|h| Polynomial::new(h).unwrap().degree())
+            .collect();
+
+        assert_eq!(degrees, vec![1, 2, 3]);
+
+        let third = Polynomial::new(&histories[2]).unwrap();
+
+        // 10 13 16 21 30 45, going both ways
+        assert_eq!(third.at(0), Some(10));
+        assert_eq!(third.at(6), Some(68));
+        assert_eq!(third.at(-1), Some(5));
+        assert_eq!(third.at(-2), Some(-4));
+
+        // n^2 + 1
+        let squares = Polynomial::new(&[1, 2, 5, 10]).unwrap();
+
+        assert_eq!(squares.degree(), 2);
+        assert_eq!(squares.at(100), Some(10001));
+        assert_eq!(squares.at(-100), Some(10001));
+        assert_eq!(squares.at(isize::MAX), None);
+
+        assert_eq!(Polynomial::new(&[0, 0, 0]).unwrap().degree(), 0);
+        assert_eq!(Polynomial::new(&[0]).unwrap().at(5), Some(0));
+    }
+
+    #[test]
+    fn test_not_polynomial() {
+        // powers of two never run out of differences
+        assert_eq!(
+            Polynomial::new(&[1, 2, 4, 8, 16]),
+            Err("history of 5 isn't a polynomial".to_string())
+        );
+        assert_eq!(Polynomial::new(&[]), Err("empty history".to_string()));
+        // could be anything after one value
+        assert!(Polynomial::new(&[7]).is_err());
+    }
+
     #[test]
     fn test_part_one() {
         let histories = parse(EXAMPLE);
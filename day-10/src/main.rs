@@ -1,6 +1,8 @@
-use std::{ time::Instant, fs, collections::HashMap, vec };
+use std::{ env, time::Instant, fs, collections::HashMap, vec };
 use lib::{ get_part, Point };
 
+mod tiles;
+
 struct CellValue {
     char: char,
     // couldn't do [Cell, 2] :(
@@ -10,6 +12,8 @@ struct CellValue {
 struct Grid {
     cells: HashMap<Point, CellValue>,
     starting_cell: Point,
+    // the pipe that S is hiding
+    start_shape: char,
 }
 
 // hey! another lifetime!
@@ -136,17 +140,17 @@ impl Grid {
             value.neighbours = neighbours;
         }
 
-        // find starting point neighbours
+        // find starting point neighbours: the ones that point back at it
         let top = starting_cell + Point { x: 0, y: -1 };
         let bottom = starting_cell + Point { x: 0, y: 1 };
         let left = starting_cell + Point { x: -1, y: 0 };
         let right = starting_cell + Point { x: 1, y: 0 };
 
-        let starting_point_neighbours: Vec<Point> = [top, bottom, left, right]
+        let candidates: Vec<Point> = [top, bottom, left, right]
             .iter()
             .filter_map(|neigh| {
-                if let Some(v2) = cells.get(&neigh) {
-                    if v2.neighbours.iter().any(|x| *x == starting_cell) {
+                if let Some(v2) = cells.get(neigh) {
+                    if v2.neighbours.contains(&starting_cell) {
                         return Some(*neigh);
                     }
                 }
@@ -154,12 +158,69 @@ impl Grid {
             })
             .collect();
 
+        // junk pipes can point at S too, so keep the two that make a loop
+        let starting_point_neighbours = candidates
+            .iter()
+            .find_map(|first| {
+                let last = Self::loop_back(&cells, starting_cell, *first)?;
+
+                (last != *first && candidates.contains(&last)).then_some(vec![*first, last])
+            })
+            .expect("a loop through S");
+
+        let start_shape = Self::shape(starting_cell, &starting_point_neighbours);
+
         if let Some(v) = cells.get_mut(&starting_cell) {
             v.neighbours = starting_point_neighbours;
         }
 
         // first time returning Self?
-        Self { cells, starting_cell }
+        Self { cells, starting_cell, start_shape }
+    }
+
+    // follows the pipes from S through first; the cell it gets back to S from, if it does
+    fn loop_back(cells: &HashMap<Point, CellValue>, start: Point, first: Point) -> Option<Point> {
+        let mut prev = start;
+        let mut cur = first;
+
+        for _ in 0..cells.len() {
+            if cur == start {
+                return Some(prev);
+            }
+
+            let neighbours = &cells.get(&cur)?.neighbours;
+
+            // a dead end, or a pipe that doesn't connect back
+            if !neighbours.contains(&prev) {
+                return None;
+            }
+
+            let next = *neighbours.iter().find(|x| **x != prev)?;
+
+            prev = cur;
+            cur = next;
+        }
+
+        None
+    }
+
+    // which pipe connects a cell to these two neighbours
+    fn shape(cell: Point, neighbours: &[Point]) -> char {
+        let dirs: Vec<(isize, isize)> = neighbours
+            .iter()
+            .map(|n| (n.x - cell.x, n.y - cell.y))
+            .collect();
+        let has = |d: (isize, isize)| dirs.contains(&d);
+
+        match (has((0, -1)), has((0, 1)), has((-1, 0)), has((1, 0))) {
+            (true, true, _, _) => '|',
+            (_, _, true, true) => '-',
+            (true, _, _, true) => 'L',
+            (true, _, true, _) => 'J',
+            (_, true, true, _) => '7',
+            (_, true, _, true) => 'F',
+            _ => panic!("S isn't a pipe: {:?}", dirs),
+        }
     }
 
     fn get_cell(&self, p: &Point) -> &CellValue {
@@ -213,6 +274,15 @@ fn main() {
 
     let grid = Grid::new(&contents);
 
+    // cargo run -- tiles
+    if env::args().any(|arg| arg == "tiles") {
+        println!("{}", grid.render());
+        println!("S is {}, the loop is {} cells", grid.start_shape, grid.loop_cells().len());
+        println!("{} inside, {} by Pick's theorem", grid.inside_count(), part_two(&grid));
+
+        return;
+    }
+
     if one {
         let now = Instant::now();
         let ans = part_one(&grid);
@@ -242,7 +312,7 @@ mod tests {
         assert_eq!(ans, 8);
     }
 
-    const EXAMPLE_2: &str =
+    pub(crate) const EXAMPLE_2: &str =
         "FF7FSF7F7F7F7F7F---7
 L|LJ||||||||||||F--J
 FL-7LJLJ||||||LJL-77
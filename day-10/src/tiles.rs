@@ -0,0 +1,164 @@
+// which tiles are part of the loop, and which are inside it
+
+use std::collections::{ HashMap, HashSet };
+use lib::Point;
+use crate::{ Grid, GridLoop };
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Tile {
+    Loop,
+    Inside,
+    Outside,
+}
+
+impl Grid {
+    pub(crate) fn loop_cells(&self) -> HashSet<Point> {
+        GridLoop::new(self).collect()
+    }
+
+    // the size of the grid, as (width, height)
+    fn size(&self) -> (isize, isize) {
+        self.cells
+            .keys()
+            .fold((0, 0), |(w, h), p| (w.max(p.x + 1), h.max(p.y + 1)))
+    }
+
+    // every tile, scanning each row and flipping inside/outside at each wall
+    pub(crate) fn classify(&self) -> HashMap<Point, Tile> {
+        let on_loop = self.loop_cells();
+        let (width, height) = self.size();
+        let mut tiles = HashMap::new();
+
+        for y in 0..height {
+            let mut inside = false;
+
+            for x in 0..width {
+                let p = Point { x, y };
+
+                if !on_loop.contains(&p) {
+                    // junk pipes count as ground
+                    tiles.insert(p, if inside { Tile::Inside } else { Tile::Outside });
+                    continue;
+                }
+
+                let char = if p == self.starting_cell {
+                    self.start_shape
+                } else {
+                    self.get_cell(&p).char
+                };
+
+                // only pipes going up are walls; F--J is one, F--7 isn't
+                if matches!(char, '|' | 'L' | 'J') {
+                    inside = !inside;
+                }
+
+                tiles.insert(p, Tile::Loop);
+            }
+        }
+
+        tiles
+    }
+
+    pub(crate) fn inside_count(&self) -> usize {
+        self.classify()
+            .values()
+            .filter(|t| **t == Tile::Inside)
+            .count()
+    }
+
+    // the loop as it is, I for inside and O for outside
+    pub(crate) fn render(&self) -> String {
+        let tiles = self.classify();
+        let (width, height) = self.size();
+
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let p = Point { x, y };
+
+                        match tiles[&p] {
+                            Tile::Loop => self.get_cell(&p).char,
+                            Tile::Inside => 'I',
+                            Tile::Outside => 'O',
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ part_two, tests::EXAMPLE_2 };
+
+    const EXAMPLE: &str = include_str!("./example.txt");
+
+    #[test]
+    fn test_start_shape() {
+        assert_eq!(Grid::new(EXAMPLE).start_shape, 'F');
+        assert_eq!(Grid::new(EXAMPLE_2).start_shape, '7');
+    }
+
+    #[test]
+    fn test_junk_next_to_start() {
+        // the | above points at S, but goes off the edge
+        let grid = Grid::new("\
+..|..
+F-S-7
+|...|
+L---J");
+
+        assert_eq!(grid.start_shape, '-');
+        assert_eq!(grid.loop_cells().len(), 12);
+    }
+
+    #[test]
+    fn test_loop_cells() {
+        let grid = Grid::new(EXAMPLE);
+        let cells = grid.loop_cells();
+
+        assert_eq!(cells.len(), 16);
+        assert!(cells.contains(&Point { x: 0, y: 2 }));
+        // junk in the corner
+        assert!(!cells.contains(&Point { x: 0, y: 0 }));
+    }
+
+    #[test]
+    fn test_classify() {
+        let grid = Grid::new("\
+...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........");
+
+        assert_eq!(grid.render(), "\
+OOOOOOOOOOO
+OS-------7O
+O|F-----7|O
+O||OOOOO||O
+O||OOOOO||O
+O|L-7OF-J|O
+O|II|O|II|O
+OL--JOL--JO
+OOOOOOOOOOO");
+        assert_eq!(grid.inside_count(), 4);
+    }
+
+    #[test]
+    fn test_matches_picks() {
+        for contents in [EXAMPLE, EXAMPLE_2] {
+            let grid = Grid::new(contents);
+
+            assert_eq!(grid.inside_count() as isize, part_two(&grid));
+        }
+    }
+}
@@ -1,7 +1,41 @@
-use std::{ time::Instant, fs };
-use lib::get_part;
+use std::{ env, time::Instant, fs, fmt };
+use lib::{ get_part, Point };
 
-type Point = (usize, usize);
+// the sum of distances, as base + gaps * (factor - 1)
+#[derive(Debug, PartialEq)]
+struct Linear {
+    base: usize,
+    gaps: usize,
+}
+
+impl Linear {
+    // factor is how many lines each empty one becomes, so at least 1
+    fn at(&self, factor: usize) -> usize {
+        assert!(factor >= 1, "expansion factor must be at least 1");
+
+        self.base + self.gaps * (factor - 1)
+    }
+}
+
+impl fmt::Display for Linear {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} + {} * (factor - 1)", self.base, self.gaps)
+    }
+}
+
+// sum of |a - b| over every pair, once they're sorted
+fn pair_sum(sorted: &[usize]) -> usize {
+    let mut total = 0;
+    let mut before = 0;
+
+    for (i, n) in sorted.iter().enumerate() {
+        // n is bigger than (or the same as) all i before it
+        total += n * i - before;
+        before += n;
+    }
+
+    total
+}
 
 struct Universe {
     grid: Vec<Point>,
@@ -23,20 +57,20 @@ impl Universe {
             width = line.len();
             for (x, char) in line.chars().enumerate() {
                 if char == '#' {
-                    grid.push((x, y));
+                    grid.push(Point { x: x as isize, y: y as isize });
                 }
             }
         }
 
         for x in 0..width {
-            if grid.iter().any(|(px, _)| *px == x) {
+            if grid.iter().any(|p| p.x == x as isize) {
                 continue;
             }
             empty_cols.push(x);
         }
 
         for y in 0..height {
-            if grid.iter().any(|(_, py)| *py == y) {
+            if grid.iter().any(|p| p.y == y as isize) {
                 continue;
             }
             empty_rows.push(y);
@@ -45,33 +79,64 @@ impl Universe {
         Self { grid, empty_cols, empty_rows }
     }
 
+    // each galaxy's (coordinate, empty lines before it) on one axis, sorted
+    fn axis(&self, get: fn(&Point) -> isize, empty: &[usize]) -> (Vec<usize>, Vec<usize>) {
+        let mut coords: Vec<usize> = self.grid.iter().map(|p| get(p) as usize).collect();
+
+        coords.sort();
+
+        let gaps = coords
+            .iter()
+            .map(|c| empty.partition_point(|e| e < c))
+            .collect();
+
+        (coords, gaps)
+    }
+
+    // every pair at once, for any expansion factor
+    fn distance_sum(&self) -> Linear {
+        let (xs, x_gaps) = self.axis(|p| p.x, &self.empty_cols);
+        let (ys, y_gaps) = self.axis(|p| p.y, &self.empty_rows);
+
+        Linear {
+            base: pair_sum(&xs) + pair_sum(&ys),
+            gaps: pair_sum(&x_gaps) + pair_sum(&y_gaps),
+        }
+    }
+
     fn get_manhattan_distance(&self, gap_distance: usize) -> usize {
-        (0..self.grid.len() - 1)
-            .flat_map(|i| {
-                (i + 1..self.grid.len()).map(move |j| {
-                    let dx = self.grid[i].0.abs_diff(self.grid[j].0);
-                    let dy = self.grid[i].1.abs_diff(self.grid[j].1);
-                    let min_x = self.grid[i].0.min(self.grid[j].0);
-                    let min_y = self.grid[i].1.min(self.grid[j].1);
-                    let max_x = min_x + dx;
-                    let max_y = min_y + dy;
-
-                    let empty_x = self.empty_cols
-                        .iter()
-                        .filter(|x| **x >= min_x && **x <= max_x)
-                        .count();
-                    let empty_y = self.empty_rows
-                        .iter()
-                        .filter(|y| **y >= min_y && **y <= max_y)
-                        .count();
-
-                    let gap = gap_distance - 1;
-                    let dist = dx + empty_x * gap + dy + empty_y * gap;
-
-                    return dist;
-                })
-            })
-            .sum()
+        self.distance_sum().at(gap_distance)
+    }
+
+    // where a galaxy ends up once every empty line is factor (at least 1) lines wide
+    fn expanded(&self, i: usize, factor: usize) -> Point {
+        assert!(factor >= 1, "expansion factor must be at least 1");
+
+        let p = self.grid[i];
+        let grow = |c: isize, empty: &[usize]| {
+            c + (empty.partition_point(|e| (*e as isize) < c) * (factor - 1)) as isize
+        };
+
+        Point { x: grow(p.x, &self.empty_cols), y: grow(p.y, &self.empty_rows) }
+    }
+
+    fn distance(&self, i: usize, j: usize, factor: usize) -> usize {
+        let (a, b) = (self.expanded(i, factor), self.expanded(j, factor));
+
+        a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+    }
+
+    // (galaxy, distance) for the k closest to galaxy i, closest first
+    fn nearest(&self, i: usize, k: usize, factor: usize) -> Vec<(usize, usize)> {
+        let mut others: Vec<(usize, usize)> = (0..self.grid.len())
+            .filter(|j| *j != i)
+            .map(|j| (j, self.distance(i, j, factor)))
+            .collect();
+
+        others.sort_by_key(|(j, dist)| (*dist, *j));
+        others.truncate(k);
+
+        others
     }
 }
 
@@ -88,6 +153,20 @@ fn main() {
     let start = Instant::now();
     let contents = fs::read_to_string("./src/input.txt").unwrap();
     let universe = Universe::new(&contents);
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // cargo run -- nearest 3 5: the 5 galaxies closest to galaxy 3, after part two's expansion
+    if args.first().is_some_and(|arg| arg == "nearest") {
+        let arg = |i: usize, default: usize| args.get(i).and_then(|n| n.parse().ok()).unwrap_or(default);
+
+        for (j, dist) in universe.nearest(arg(1, 0), arg(2, 5), 1000000) {
+            println!("{} {:?}: {}", j, universe.grid[j], dist);
+        }
+
+        println!("all pairs: {}", universe.distance_sum());
+
+        return;
+    }
 
     if one {
         let now = Instant::now();
@@ -138,6 +217,57 @@ mod tests {
         assert_eq!(ans, 374);
     }
 
+    #[test]
+    fn test_linear() {
+        let grid = Universe::new(EXAMPLE);
+        let sum = grid.distance_sum();
+
+        assert_eq!(sum.to_string(), "292 + 82 * (factor - 1)");
+        assert_eq!(sum.at(1), 292);
+        assert_eq!(sum.at(2), 374);
+    }
+
+    #[test]
+    #[should_panic(expected = "expansion factor must be at least 1")]
+    fn test_no_factor() {
+        Universe::new(EXAMPLE).get_manhattan_distance(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "expansion factor must be at least 1")]
+    fn test_no_factor_pair() {
+        Universe::new(EXAMPLE).distance(0, 1, 0);
+    }
+
+    #[test]
+    fn test_pairs() {
+        let grid = Universe::new(EXAMPLE);
+
+        // the puzzle's galaxies 5 and 9, 1 and 7, 3 and 6, 8 and 9
+        assert_eq!(grid.distance(4, 8, 2), 9);
+        assert_eq!(grid.distance(0, 6, 2), 15);
+        assert_eq!(grid.distance(2, 5, 2), 17);
+        assert_eq!(grid.distance(7, 8, 2), 5);
+
+        // adding up every pair gets the same as the sum
+        for factor in [1, 2, 10, 100] {
+            let total: usize = (0..9)
+                .flat_map(|i| (i + 1..9).map(move |j| (i, j)))
+                .map(|(i, j)| grid.distance(i, j, factor))
+                .sum();
+
+            assert_eq!(total, grid.get_manhattan_distance(factor));
+        }
+    }
+
+    #[test]
+    fn test_nearest() {
+        let grid = Universe::new(EXAMPLE);
+
+        assert_eq!(grid.nearest(7, 2, 2), vec![(8, 5), (4, 6)]);
+        assert_eq!(grid.nearest(7, 20, 2).len(), 8);
+    }
+
     #[test]
     fn test_part_two() {
         let grid = Universe::new(EXAMPLE);
@@ -0,0 +1,249 @@
+// actually look at the arrangements, instead of just counting them
+//
+// cargo run -- arrangements 3            (every arrangement of row 3, counting from 0)
+// cargo run -- arrangements 3 1234       (just the 1234th)
+// cargo run -- arrangements 3 sample 5 7 (5 at random, seeded with 7)
+
+use std::collections::HashMap;
+use crate::{ Condition, Spring };
+
+type Memo = HashMap<(usize, usize, usize), u128>;
+
+impl Spring {
+    // every arrangement, in the same order as their strings sort ('#' < '.')
+    pub(crate) fn arrangements_iter(&self) -> Arrangements<'_> {
+        let mut memo = HashMap::new();
+        let total = self.count_from(0, 0, 0, &mut memo);
+
+        Arrangements { spring: self, memo, index: 0, total }
+    }
+
+    // the k-th arrangement, counting from 0
+    pub(crate) fn nth_arrangement(&self, k: u128) -> Option<Spring> {
+        self.nth_with_memo(k, &mut HashMap::new())
+    }
+
+    // every arrangement is equally likely
+    pub(crate) fn sample_arrangement(&self, rng: &mut Rng) -> Option<Spring> {
+        let mut memo = HashMap::new();
+        let total = self.count_from(0, 0, 0, &mut memo);
+
+        if total == 0 {
+            return None;
+        }
+
+        self.nth_with_memo(rng.below(total), &mut memo)
+    }
+
+    // walk the row, using the counts to skip over whole
+    // subtrees of arrangements that come before k
+    fn nth_with_memo(&self, mut k: u128, memo: &mut Memo) -> Option<Spring> {
+        let mut list = vec![];
+        let mut group = 0;
+        let mut run = 0;
+
+        for pos in 0..self.list.len() {
+            let mut picked = None;
+
+            // damaged first, since '#' sorts before '.'
+            for condition in [Condition::Damaged, Condition::Operational] {
+                if self.list[pos] != Condition::Unknown && self.list[pos] != condition {
+                    continue;
+                }
+
+                let next = match self.next_state(group, run, &condition) {
+                    Some(next) => next,
+                    None => continue,
+                };
+
+                let count = self.count_from(pos + 1, next.0, next.1, memo);
+
+                if k < count {
+                    picked = Some((condition, next));
+                    break;
+                }
+
+                k -= count;
+            }
+
+            // k is too big
+            let (condition, next) = picked?;
+
+            list.push(condition);
+            (group, run) = next;
+        }
+
+        if k != 0 || !self.is_finished(group, run) {
+            return None;
+        }
+
+        Some(Spring::new(list, self.damaged_sizes.clone()))
+    }
+}
+
+pub(crate) struct Arrangements<'a> {
+    spring: &'a Spring,
+    // counts are shared by every arrangement we look up
+    memo: Memo,
+    index: u128,
+    total: u128,
+}
+
+impl<'a> Iterator for Arrangements<'a> {
+    type Item = Spring;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.total {
+            return None;
+        }
+
+        let next = self.spring.nth_with_memo(self.index, &mut self.memo);
+        self.index += 1;
+
+        next
+    }
+}
+
+// xorshift; good enough for picking test rows
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // can't be zero
+        Self(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+
+        x
+    }
+
+    // uniform in 0..n; throw away the uneven bit at the top
+    fn below(&mut self, n: u128) -> u128 {
+        let zone = u128::MAX - u128::MAX % n;
+
+        loop {
+            let r = ((self.next_u64() as u128) << 64) | self.next_u64() as u128;
+
+            if r < zone {
+                return r % n;
+            }
+        }
+    }
+}
+
+// `args` is everything after "arrangements"
+pub(crate) fn run(springs: &[Spring], args: &[String]) {
+    let number = |i: usize, default: u128| {
+        args.get(i).map_or(default, |n| n.parse().expect("a number"))
+    };
+    let spring = &springs[number(0, 0) as usize];
+    let total = spring.arrangements();
+
+    println!("{} has {} arrangements", spring, total);
+
+    match args.get(1).map(|a| a.as_str()) {
+        None => {
+            for (i, arrangement) in spring.arrangements_iter().enumerate() {
+                println!("{}: {}", i, arrangement);
+            }
+        }
+        Some("sample") => {
+            let mut rng = Rng::new(number(3, 2023) as u64);
+
+            for _ in 0..number(2, 1) {
+                match spring.sample_arrangement(&mut rng) {
+                    Some(arrangement) => println!("{}", arrangement),
+                    None => break,
+                }
+            }
+        }
+        Some(_) => {
+            let k = number(1, 0);
+
+            match spring.nth_arrangement(k) {
+                Some(arrangement) => println!("{}: {}", k, arrangement),
+                None => println!("there's no arrangement {}", k),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ parse_springs, parse_springs_five_times };
+
+    const EXAMPLE: &str = include_str!("./example.txt");
+
+    #[test]
+    fn test_arrangements_iter() {
+        let springs = parse_springs("?###???????? 3,2,1");
+        let all: Vec<String> = springs[0]
+            .arrangements_iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(all.len(), 10);
+        assert_eq!(all[0], ".###.##.#...");
+        assert_eq!(all[9], ".###....##.#");
+
+        // sorted, no duplicates
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+
+        for s in all.iter() {
+            let spring = parse_springs(&format!("{} 3,2,1", s));
+
+            assert!(!spring[0].is_invalid());
+        }
+    }
+
+    #[test]
+    fn test_count_matches() {
+        for spring in parse_springs(EXAMPLE) {
+            assert_eq!(spring.arrangements_iter().count() as u128, spring.arrangements());
+        }
+    }
+
+    #[test]
+    fn test_nth_arrangement() {
+        let springs = parse_springs_five_times(EXAMPLE);
+        let spring = &springs[5];
+        let total = spring.arrangements();
+
+        assert_eq!(total, 506250);
+
+        let last = spring.nth_arrangement(total - 1).unwrap();
+
+        assert!(!last.is_invalid());
+        assert_eq!(spring.nth_arrangement(total), None);
+
+        // same as going through the iterator
+        let mut iter = spring.arrangements_iter();
+        assert_eq!(iter.nth(1234), spring.nth_arrangement(1234));
+    }
+
+    #[test]
+    fn test_sample() {
+        let springs = parse_springs(".??..??...?##. 1,1,3");
+        let mut rng = Rng::new(2023);
+        let mut seen = HashMap::new();
+
+        for _ in 0..400 {
+            let sample = springs[0].sample_arrangement(&mut rng).unwrap();
+            *seen.entry(sample.to_string()).or_insert(0) += 1;
+        }
+
+        // all 4, roughly evenly
+        assert_eq!(seen.len(), 4);
+        assert!(seen.values().all(|n| *n > 60));
+
+        let impossible = parse_springs("#.# 3");
+        assert_eq!(impossible[0].sample_arrangement(&mut rng), None);
+    }
+}
@@ -1,6 +1,9 @@
-use std::{ fmt, fs, time::Instant };
+use std::{ env, fmt, fs, time::Instant, collections::HashMap };
 use lib::get_part;
 
+mod arrangements;
+mod nonogram;
+
 // I think this is basically a nonogram
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 enum Condition {
@@ -17,6 +20,14 @@ struct Spring {
 }
 
 impl Spring {
+    fn new(list: Vec<Condition>, damaged_sizes: Vec<usize>) -> Self {
+        let damaged_count = damaged_sizes.iter().sum();
+
+        Self { list, damaged_sizes, damaged_count }
+    }
+
+    // only the tests and bfs use this; too slow for counting
+    #[cfg(test)]
     fn is_invalid(&self) -> bool {
         let mut consecutive = vec![];
         let mut cur = 0;
@@ -66,6 +77,87 @@ impl Spring {
 
         &consecutive != &self.damaged_sizes
     }
+
+    fn arrangements(&self) -> u128 {
+        let mut memo = HashMap::new();
+
+        self.count_from(0, 0, 0, &mut memo)
+    }
+
+    // how many ways to finish the row from `pos`, when we're on group
+    // `group` and have already seen `run` damaged in a row
+    fn count_from(
+        &self,
+        pos: usize,
+        group: usize,
+        run: usize,
+        memo: &mut HashMap<(usize, usize, usize), u128>
+    ) -> u128 {
+        if pos == self.list.len() {
+            return if self.is_finished(group, run) { 1 } else { 0 };
+        }
+
+        if let Some(count) = memo.get(&(pos, group, run)) {
+            return *count;
+        }
+
+        let mut count = 0;
+
+        if self.list[pos] != Condition::Operational {
+            if let Some(next) = self.next_state(group, run, &Condition::Damaged) {
+                count += self.count_from(pos + 1, next.0, next.1, memo);
+            }
+        }
+
+        if self.list[pos] != Condition::Damaged {
+            if let Some(next) = self.next_state(group, run, &Condition::Operational) {
+                count += self.count_from(pos + 1, next.0, next.1, memo);
+            }
+        }
+
+        memo.insert((pos, group, run), count);
+
+        count
+    }
+
+    // (group, run) after putting `condition` in the next spot, if it's allowed
+    fn next_state(
+        &self,
+        group: usize,
+        run: usize,
+        condition: &Condition
+    ) -> Option<(usize, usize)> {
+        match condition {
+            Condition::Damaged => {
+                let size = self.damaged_sizes.get(group)?;
+
+                if run < *size {
+                    Some((group, run + 1))
+                } else {
+                    None
+                }
+            }
+            Condition::Operational => {
+                if run == 0 {
+                    Some((group, 0))
+                } else if run == self.damaged_sizes[group] {
+                    // group is done
+                    Some((group + 1, 0))
+                } else {
+                    None
+                }
+            }
+            Condition::Unknown => None,
+        }
+    }
+
+    // at the end of the row, every group needs to be used up
+    fn is_finished(&self, group: usize, run: usize) -> bool {
+        let groups = self.damaged_sizes.len();
+
+        (run == 0 && group == groups) ||
+            (group + 1 == groups && run == self.damaged_sizes[group])
+    }
 }
 
 impl fmt::Display for Spring {
@@ -113,13 +205,7 @@ fn parse_springs(data: &str) -> Vec<Spring> {
                 .map(|group| group.parse().expect("didn't get group"))
                 .collect();
 
-            let damaged_count = damaged_sizes.iter().sum();
-
-            Spring {
-                list,
-                damaged_sizes,
-                damaged_count,
-            }
+            Spring::new(list, damaged_sizes)
         })
         .collect()
 }
@@ -161,23 +247,20 @@ fn parse_springs_five_times(data: &str) -> Vec<Spring> {
                 .map(|group| group.parse().expect("didn't get group"))
                 .collect();
 
-            let damaged_count = damaged_sizes.iter().sum();
-
-            Spring {
-                list,
-                damaged_sizes,
-                damaged_count,
-            }
+            Spring::new(list, damaged_sizes)
         })
         .collect()
 }
 
+// only bfs and dfs step through states
+#[cfg(test)]
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct State {
     spring: Spring,
     index: isize,
 }
 
+#[cfg(test)]
 impl fmt::Display for State {
     // This trait requires `fmt` with this exact signature.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -189,6 +272,8 @@ impl fmt::Display for State {
     }
 }
 
+// slow, but the tests check `arrangements` against it
+#[cfg(test)]
 fn bfs(spring: &Spring) -> isize {
     let mut count = 0;
     let mut pq = vec![];
@@ -234,6 +319,8 @@ fn bfs(spring: &Spring) -> isize {
     count
 }
 
+// unfinished; only test_dfs (ignored) calls it
+#[cfg(test)]
 fn dfs(spring: &Spring) -> isize {
     let index = spring.list
         .iter()
@@ -268,6 +355,7 @@ fn dfs(spring: &Spring) -> isize {
 }
 
 // state at this given index is a question mark
+#[cfg(test)]
 fn get_next_states(state: State) -> Vec<State> {
     let index =
         (
@@ -316,23 +404,39 @@ fn get_next_states(state: State) -> Vec<State> {
     }
 }
 
-fn part_one(springs: &Vec<Spring>) -> isize {
-    springs.iter().map(bfs).sum()
+fn part_one(springs: &Vec<Spring>) -> u128 {
+    springs.iter().map(Spring::arrangements).sum()
 }
 
-fn part_two(input: &str) -> isize {
+fn part_two(input: &str) -> u128 {
     let springs = parse_springs_five_times(input);
 
-    springs.iter().map(bfs).sum()
+    springs.iter().map(Spring::arrangements).sum()
 }
 
 fn main() {
     let (one, two) = get_part();
     let start = Instant::now();
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // cargo run -- nonogram <rows> <columns>; doesn't need the input
+    if args.first().is_some_and(|arg| arg == "nonogram") {
+        nonogram::run(&args[1..]);
+
+        return;
+    }
+
     let contents = fs::read_to_string("./src/input.txt").unwrap();
 
     let springs = parse_springs(&contents);
 
+    // cargo run -- arrangements <row> [k | sample [n] [seed]]
+    if args.first().is_some_and(|arg| arg == "arrangements") {
+        arrangements::run(&springs, &args[1..]);
+
+        return;
+    }
+
     if one {
         let now = Instant::now();
         let ans = part_one(&springs);
@@ -439,7 +543,32 @@ mod tests {
         assert_eq!(ans, 16);
     }
 
-    #[ignore = "This doesn't work"]
+    #[test]
+    fn test_arrangements() {
+        // each example row on its own
+        let expected = [1, 4, 1, 1, 4, 10];
+
+        for (spring, count) in parse_springs(EXAMPLE).iter().zip(expected) {
+            assert_eq!(spring.arrangements(), count, "{}", spring);
+            assert_eq!(spring.arrangements(), bfs(spring) as u128, "{}", spring);
+        }
+
+        let springs = parse_springs("??? 1,1\n?.?? 1,1\n??#?? 2\n.??#?.?? 1,2");
+
+        for spring in springs.iter() {
+            assert_eq!(spring.arrangements(), bfs(spring) as u128, "{}", spring);
+        }
+    }
+
+    #[test]
+    fn test_arrangements_five_times() {
+        let expected = [1, 16384, 1, 16, 2500, 506250];
+
+        for (spring, count) in parse_springs_five_times(EXAMPLE).iter().zip(expected) {
+            assert_eq!(spring.arrangements(), count, "{}", spring);
+        }
+    }
+
     #[test]
     fn test_part_two() {
         let ans = part_two(EXAMPLE);
@@ -0,0 +1,258 @@
+// a Spring is just one line of a nonogram, so solve the whole grid with them
+//
+// cargo run -- nonogram "1,1 5 5 3 1" "2 4 4 4 2"
+// rows, then columns; each line's clues split by commas, and 0 for an empty line
+
+use std::fmt;
+use crate::{ Condition, Spring };
+
+type Grid = Vec<Vec<Condition>>;
+
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    cols: Vec<Vec<usize>>,
+}
+
+#[derive(Debug)]
+pub struct Solution {
+    grid: Grid,
+    rows: Vec<Vec<usize>>,
+    // false if there's more than one way to fill the grid
+    pub unique: bool,
+}
+
+impl Nonogram {
+    pub fn new(rows: Vec<Vec<usize>>, cols: Vec<Vec<usize>>) -> Self {
+        Self { rows, cols }
+    }
+
+    pub fn solve(&self) -> Option<Solution> {
+        let grid = vec![vec![Condition::Unknown; self.cols.len()]; self.rows.len()];
+        let mut found = vec![];
+
+        // stop at two: that's enough to know it isn't unique
+        self.search(grid, &mut found, 2);
+
+        let unique = found.len() == 1;
+
+        found.into_iter().next().map(|grid| Solution {
+            grid,
+            rows: self.rows.clone(),
+            unique,
+        })
+    }
+
+    fn search(&self, grid: Grid, found: &mut Vec<Grid>, limit: usize) {
+        if found.len() >= limit {
+            return;
+        }
+
+        let grid = match self.line_solve(grid) {
+            Some(grid) => grid,
+            // contradiction
+            None => return,
+        };
+
+        let next = grid
+            .iter()
+            .enumerate()
+            .find_map(|(y, row)| {
+                row.iter().position(|c| *c == Condition::Unknown).map(|x| (x, y))
+            });
+
+        let (x, y) = match next {
+            Some(cell) => cell,
+            None => {
+                found.push(grid);
+                return;
+            }
+        };
+
+        // line solving got stuck, so guess
+        for guess in [Condition::Damaged, Condition::Operational] {
+            let mut next = grid.clone();
+            next[y][x] = guess;
+
+            self.search(next, found, limit);
+        }
+    }
+
+    // keep filling in whatever the rows and columns force,
+    // until nothing changes
+    fn line_solve(&self, mut grid: Grid) -> Option<Grid> {
+        loop {
+            let mut changed = false;
+
+            for (y, clues) in self.rows.iter().enumerate() {
+                let line = solve_line(&grid[y], clues)?;
+
+                if line != grid[y] {
+                    grid[y] = line;
+                    changed = true;
+                }
+            }
+
+            for (x, clues) in self.cols.iter().enumerate() {
+                let col: Vec<Condition> = grid.iter().map(|row| row[x].clone()).collect();
+                let line = solve_line(&col, clues)?;
+
+                if line != col {
+                    for (y, c) in line.into_iter().enumerate() {
+                        grid[y][x] = c;
+                    }
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                return Some(grid);
+            }
+        }
+    }
+}
+
+// fill in every unknown that's the same in all of the line's arrangements
+fn solve_line(line: &[Condition], clues: &[usize]) -> Option<Vec<Condition>> {
+    let mut spring = Spring::new(line.to_vec(), clues.to_vec());
+    let total = spring.arrangements();
+
+    if total == 0 {
+        return None;
+    }
+
+    let mut solved = line.to_vec();
+
+    for (i, condition) in line.iter().enumerate() {
+        if *condition != Condition::Unknown {
+            continue;
+        }
+
+        spring.list[i] = Condition::Damaged;
+        let damaged = spring.arrangements();
+        spring.list[i] = Condition::Unknown;
+
+        if damaged == 0 {
+            solved[i] = Condition::Operational;
+        } else if damaged == total {
+            solved[i] = Condition::Damaged;
+        }
+    }
+
+    Some(solved)
+}
+
+impl Solution {
+    #[cfg(test)]
+    pub fn get(&self, x: usize, y: usize) -> &Condition {
+        &self.grid[y][x]
+    }
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (row, clues) in self.grid.iter().zip(self.rows.iter()) {
+            writeln!(f, "{}", Spring::new(row.clone(), clues.clone()))?;
+        }
+
+        Ok(())
+    }
+}
+
+// `args` is everything after "nonogram"
+pub(crate) fn run(args: &[String]) {
+    let clues = |i: usize| -> Vec<Vec<usize>> {
+        args.get(i)
+            .expect("rows, then columns")
+            .split_whitespace()
+            .map(|line| {
+                line.split(',')
+                    .map(|n| n.parse().expect("clues are numbers"))
+                    .filter(|n| *n > 0)
+                    .collect()
+            })
+            .collect()
+    };
+
+    match Nonogram::new(clues(0), clues(1)).solve() {
+        Some(solution) => {
+            print!("{}", solution);
+
+            if !solution.unique {
+                println!("(but that's not the only way)");
+            }
+        }
+        None => println!("there's no way to fill it in"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_line() {
+        // 3 in 5 has to cover the middle
+        let line = solve_line(&vec![Condition::Unknown; 5], &[3]).unwrap();
+
+        assert_eq!(line[2], Condition::Damaged);
+        assert_eq!(line[0], Condition::Unknown);
+
+        // doesn't fit
+        assert_eq!(solve_line(&vec![Condition::Unknown; 2], &[3]), None);
+    }
+
+    #[test]
+    fn test_heart() {
+        let nonogram = Nonogram::new(
+            vec![vec![1, 1], vec![5], vec![5], vec![3], vec![1]],
+            vec![vec![2], vec![4], vec![4], vec![4], vec![2]]
+        );
+
+        let solution = nonogram.solve().expect("should solve");
+
+        assert!(solution.unique);
+        assert_eq!(solution.get(0, 0), &Condition::Operational);
+        assert_eq!(solution.get(1, 0), &Condition::Damaged);
+        assert_eq!(
+            solution.to_string(),
+            ".#.#.\n#####\n#####\n.###.\n..#..\n"
+        );
+    }
+
+    #[test]
+    fn test_not_unique() {
+        // either diagonal works; needs a guess
+        let nonogram = Nonogram::new(
+            vec![vec![1], vec![1]],
+            vec![vec![1], vec![1]]
+        );
+
+        let solution = nonogram.solve().expect("should solve");
+
+        assert!(!solution.unique);
+        assert_eq!(solution.to_string(), "#.\n.#\n");
+    }
+
+    #[test]
+    fn test_empty_lines() {
+        let nonogram = Nonogram::new(
+            vec![vec![], vec![2]],
+            vec![vec![1], vec![1]]
+        );
+
+        let solution = nonogram.solve().expect("should solve");
+
+        assert!(solution.unique);
+        assert_eq!(solution.to_string(), "..\n##\n");
+    }
+
+    #[test]
+    fn test_impossible() {
+        let nonogram = Nonogram::new(
+            vec![vec![2], vec![]],
+            vec![vec![1], vec![]]
+        );
+
+        assert!(nonogram.solve().is_none());
+    }
+}
@@ -1,6 +1,10 @@
 use std::{ time::Instant, fs, collections::{ HashMap, BinaryHeap }, vec };
 use lib::{ get_part, Point };
 
+mod segments;
+
+use segments::Contraption;
+
 const UP: u8 = 0b1000;
 const RIGHT: u8 = 0b0100;
 const DOWN: u8 = 0b0010;
@@ -27,7 +31,7 @@ struct Tile {
     artifact: Artifact,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
 struct BeamState {
     point: Point,
     direction: u8,
@@ -258,32 +262,12 @@ fn part_one(grid: &Grid) -> usize {
 }
 
 fn part_two(grid: &Grid) -> usize {
-    let mut best = 0;
-
-    let mut run_it = |x, y, direction| {
-        let cloned = grid.traverse(BeamState {
-            direction,
-            point: Point { x: x as isize, y: y as isize },
-        });
-
-        // first time using max
-        best = best.max(cloned.get_energized());
-    };
-
-    let height = grid.cells.keys().map(|c| c.y).max().unwrap();
-    let width = grid.cells.keys().map(|c| c.x).max().unwrap();
-    
-    for x in 0..=width {
-        run_it(x, 0, DOWN);
-        run_it(x, height, UP);
-    }
-
-    for y in 0..=height {
-        run_it(0, y, RIGHT);
-        run_it(width, y, LEFT);
-    }
-    
-    best
+    Contraption::new(grid)
+        .all_energized()
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap()
 }
 
 fn main() {
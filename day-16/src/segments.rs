@@ -0,0 +1,318 @@
+// beams only branch at splitters, so work out what each splitter lights up once
+// and share it between every entry point
+
+use std::collections::{ HashMap, HashSet };
+use lib::Point;
+use crate::{ Grid, BeamState, UP, RIGHT, DOWN, LEFT };
+
+// one bit per tile
+#[derive(Debug, Clone, PartialEq)]
+struct Cells(Vec<u64>);
+
+impl Cells {
+    fn new(size: usize) -> Self {
+        Self(vec![0; size.div_ceil(64)])
+    }
+
+    fn insert(&mut self, i: usize) {
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    fn union(&mut self, other: &Cells) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a |= b;
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.0.iter().map(|n| n.count_ones() as usize).sum()
+    }
+}
+
+// the straight-ish run of a beam until it splits, leaves, or goes in circles
+struct Segment {
+    cells: Cells,
+    // the splitter it ends at
+    splitter: Option<usize>,
+}
+
+pub(crate) struct Contraption<'a> {
+    grid: &'a Grid,
+    width: isize,
+    height: isize,
+    // the splitters that beams actually split at, and their index
+    splitters: HashMap<Point, usize>,
+    // everything lit once a beam splits at each splitter
+    energized: Vec<Cells>,
+}
+
+impl<'a> Contraption<'a> {
+    pub(crate) fn new(grid: &'a Grid) -> Self {
+        let width = grid.cells.keys().map(|c| c.x).max().unwrap() + 1;
+        let height = grid.cells.keys().map(|c| c.y).max().unwrap() + 1;
+
+        // sorted, so the indexes are the same every time
+        let mut points: Vec<Point> = grid.cells
+            .keys()
+            .filter(|p| Self::split_direction(grid, **p).is_some())
+            .copied()
+            .collect();
+
+        points.sort();
+
+        let splitters = points.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+        let mut contraption = Self { grid, width, height, splitters, energized: vec![] };
+
+        // what each splitter lights itself, and which splitters it leads to
+        let mut lit = vec![];
+        let mut leads_to = vec![];
+
+        for point in points.iter() {
+            let mut cells = contraption.empty();
+            let mut next = vec![];
+
+            cells.insert(contraption.index(*point));
+
+            let direction = Self::split_direction(grid, *point).expect("a splitter");
+
+            for state in grid.get_next_states(BeamState { point: *point, direction }) {
+                let segment = contraption.segment(state);
+
+                cells.union(&segment.cells);
+                next.extend(segment.splitter);
+            }
+
+            lit.push(cells);
+            leads_to.push(next);
+        }
+
+        contraption.energized = energized(lit, &leads_to);
+
+        contraption
+    }
+
+    // a direction that makes this tile split the beam, if it's a splitter
+    fn split_direction(grid: &Grid, point: Point) -> Option<u8> {
+        [RIGHT, DOWN]
+            .into_iter()
+            .find(|direction| grid.get_next_states(BeamState { point, direction: *direction }).len() == 2)
+    }
+
+    fn empty(&self) -> Cells {
+        Cells::new((self.width * self.height) as usize)
+    }
+
+    fn index(&self, p: Point) -> usize {
+        (p.y * self.width + p.x) as usize
+    }
+
+    fn segment(&self, start: BeamState) -> Segment {
+        let mut cells = self.empty();
+        let mut seen = HashSet::new();
+        let mut state = start;
+
+        // off the grid, or around in a loop of mirrors
+        while self.grid.cells.contains_key(&state.point) && seen.insert(state) {
+            cells.insert(self.index(state.point));
+
+            let next = self.grid.get_next_states(state);
+
+            if next.len() == 2 {
+                return Segment { cells, splitter: Some(self.splitters[&state.point]) };
+            }
+
+            state = next[0];
+        }
+
+        Segment { cells, splitter: None }
+    }
+
+    pub(crate) fn energized_from(&self, start: BeamState) -> usize {
+        let mut segment = self.segment(start);
+
+        if let Some(splitter) = segment.splitter {
+            segment.cells.union(&self.energized[splitter]);
+        }
+
+        segment.cells.count()
+    }
+
+    // every tile around the edge, pointing in
+    pub(crate) fn entry_points(&self) -> Vec<BeamState> {
+        let mut entries = vec![];
+
+        for x in 0..self.width {
+            entries.push(BeamState { point: Point { x, y: 0 }, direction: DOWN });
+            entries.push(BeamState { point: Point { x, y: self.height - 1 }, direction: UP });
+        }
+
+        for y in 0..self.height {
+            entries.push(BeamState { point: Point { x: 0, y }, direction: RIGHT });
+            entries.push(BeamState { point: Point { x: self.width - 1, y }, direction: LEFT });
+        }
+
+        entries
+    }
+
+    pub(crate) fn all_energized(&self) -> Vec<(BeamState, usize)> {
+        self.entry_points()
+            .into_iter()
+            .map(|entry| (entry, self.energized_from(entry)))
+            .collect()
+    }
+}
+
+// splitters that lead to each other (in a loop) light up the same tiles, so
+// group them by strongly connected components, and fill in from the last group back
+fn energized(lit: Vec<Cells>, leads_to: &[Vec<usize>]) -> Vec<Cells> {
+    let components = tarjan(leads_to);
+    let mut component_of = vec![0; lit.len()];
+
+    for (c, members) in components.iter().enumerate() {
+        for m in members {
+            component_of[*m] = c;
+        }
+    }
+
+    // tarjan finds components after everything they lead to
+    let mut totals: Vec<Cells> = vec![];
+
+    for members in components.iter() {
+        let mut cells = lit[members[0]].clone();
+
+        for m in members {
+            cells.union(&lit[*m]);
+
+            for next in leads_to[*m].iter() {
+                let c = component_of[*next];
+
+                // the same component isn't done yet, but it's all in here anyway
+                if c < totals.len() {
+                    cells.union(&totals[c]);
+                }
+            }
+        }
+
+        totals.push(cells);
+    }
+
+    component_of.iter().map(|c| totals[*c].clone()).collect()
+}
+
+struct Tarjan<'a> {
+    edges: &'a [Vec<usize>],
+    index: Vec<Option<usize>>,
+    low: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next: usize,
+    components: Vec<Vec<usize>>,
+}
+
+// strongly connected components, each after the ones it leads to
+fn tarjan(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = edges.len();
+    let mut t = Tarjan {
+        edges,
+        index: vec![None; n],
+        low: vec![0; n],
+        on_stack: vec![false; n],
+        stack: vec![],
+        next: 0,
+        components: vec![],
+    };
+
+    for v in 0..n {
+        if t.index[v].is_none() {
+            t.connect(v);
+        }
+    }
+
+    t.components
+}
+
+impl Tarjan<'_> {
+    fn connect(&mut self, v: usize) {
+        self.index[v] = Some(self.next);
+        self.low[v] = self.next;
+        self.next += 1;
+        self.stack.push(v);
+        self.on_stack[v] = true;
+
+        for w in self.edges[v].clone() {
+            match self.index[w] {
+                None => {
+                    self.connect(w);
+                    self.low[v] = self.low[v].min(self.low[w]);
+                }
+                Some(i) if self.on_stack[w] => {
+                    self.low[v] = self.low[v].min(i);
+                }
+                _ => {}
+            }
+        }
+
+        if Some(self.low[v]) == self.index[v] {
+            let mut component = vec![];
+
+            while let Some(w) = self.stack.pop() {
+                self.on_stack[w] = false;
+                component.push(w);
+
+                if w == v {
+                    break;
+                }
+            }
+
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("./example.txt");
+
+    #[test]
+    fn test_matches_traverse() {
+        let grid = Grid::new(EXAMPLE);
+        let contraption = Contraption::new(&grid);
+        let all = contraption.all_energized();
+
+        assert_eq!(all.len(), 40);
+
+        for (entry, count) in all {
+            assert_eq!(count, grid.traverse(entry).get_energized(), "{:?}", entry);
+        }
+    }
+
+    #[test]
+    fn test_splitter_loop() {
+        // the two splitters send beams back and forth between each other
+        let grid = Grid::new("\
+.|..\\
+.....
+.\\..-
+.....");
+        let contraption = Contraption::new(&grid);
+
+        for entry in contraption.entry_points() {
+            assert_eq!(contraption.energized_from(entry), grid.traverse(entry).get_energized(), "{:?}", entry);
+        }
+    }
+
+    #[test]
+    fn test_tarjan() {
+        // 0 -> 1 -> 2 -> 0, and 2 -> 3
+        let components = tarjan(&[vec![1], vec![2], vec![0, 3], vec![]]);
+
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0], vec![3]);
+
+        let mut cycle = components[1].clone();
+        cycle.sort();
+        assert_eq!(cycle, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,257 @@
+// the workflows as one decision tree, so there's no string lookups per part
+
+use std::{ collections::HashMap, cmp::Ordering };
+use crate::{ System, Rule, Ranges, ALL_RANGES, category, split };
+
+const CATEGORIES: [&str; 4] = ["x", "m", "a", "s"];
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Accept,
+    Reject,
+    // category is an index into x, m, a, s
+    Test { category: usize, cmp: Ordering, num: usize, pass: usize, fail: usize },
+}
+
+const ACCEPT: usize = 0;
+const REJECT: usize = 1;
+
+pub(crate) struct Tree {
+    // nodes point at each other by index
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+impl System<'_> {
+    // fails if the workflows are missing something, or loop
+    pub(crate) fn compile(&self) -> Result<Tree, String> {
+        if let Some(error) = self.validate().iter().find(|f| f.is_error()) {
+            return Err(error.to_string());
+        }
+
+        let mut tree = Tree { nodes: vec![Node::Accept, Node::Reject], root: REJECT };
+        // where each workflow starts, since more than one rule can go to it
+        let mut starts = HashMap::new();
+
+        tree.root = self.compile_workflow("in", &mut tree, &mut starts);
+
+        Ok(tree)
+    }
+
+    fn compile_workflow<'a>(
+        &'a self,
+        name: &'a str,
+        tree: &mut Tree,
+        starts: &mut HashMap<&'a str, usize>
+    ) -> usize {
+        match name {
+            "A" => return ACCEPT,
+            "R" => return REJECT,
+            _ => {}
+        }
+
+        if let Some(start) = starts.get(name) {
+            return *start;
+        }
+
+        // rules that can't match would only make the tree bigger (or loop)
+        let dead = self.dead_rules(name);
+        let mut live: Vec<&Rule> = self.workflows[name].rules
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !dead.contains(i))
+            .map(|(_, rule)| rule)
+            .collect();
+
+        // nothing after the first rule without a test gets used
+        if let Some(end) = live.iter().position(|rule| rule.test.is_none()) {
+            live.truncate(end + 1);
+        }
+
+        // build from the last rule back, so each test knows where to fail to;
+        // validate makes sure everything left matches the last rule, test or not
+        let mut rules = live.into_iter().rev();
+        let last = rules.next().expect("at least one rule");
+        let mut fail = self.compile_workflow(last.goto, tree, starts);
+
+        for rule in rules {
+            let test = rule.test.as_ref().expect("a test before the last rule");
+            let pass = self.compile_workflow(rule.goto, tree, starts);
+
+            tree.nodes.push(Node::Test {
+                category: category(test.key).expect("validated category"),
+                cmp: test.cmp,
+                num: test.num,
+                pass,
+                fail,
+            });
+
+            fail = tree.nodes.len() - 1;
+        }
+
+        starts.insert(name, fail);
+
+        fail
+    }
+}
+
+impl Tree {
+    pub(crate) fn is_accepted(&self, part: &[usize; 4]) -> bool {
+        let mut cur = self.root;
+
+        loop {
+            match &self.nodes[cur] {
+                Node::Accept => return true,
+                Node::Reject => return false,
+                Node::Test { category, cmp, num, pass, fail } => {
+                    cur = if part[*category].cmp(num) == *cmp { *pass } else { *fail };
+                }
+            }
+        }
+    }
+
+    // every box of ratings that gets accepted; none of them overlap
+    pub(crate) fn accepted(&self) -> Vec<Ranges> {
+        let mut regions = vec![];
+        let mut stack = vec![(self.root, ALL_RANGES)];
+
+        while let Some((cur, ranges)) = stack.pop() {
+            match &self.nodes[cur] {
+                Node::Accept => regions.push(ranges),
+                Node::Reject => {}
+                Node::Test { category, cmp, num, pass, fail } => {
+                    let (yes, no) = split(&ranges, *category, *cmp, *num);
+
+                    // fail first, so pass gets popped first
+                    if let Some(no) = no {
+                        stack.push((*fail, no));
+                    }
+
+                    if let Some(yes) = yes {
+                        stack.push((*pass, yes));
+                    }
+                }
+            }
+        }
+
+        regions
+    }
+}
+
+pub(crate) fn combinations(ranges: &Ranges) -> usize {
+    ranges.iter().map(|(min, max)| max - min + 1).product()
+}
+
+// x 1..=1415, m 1..=4000, a 1..=2005, s 1..=1350
+pub(crate) fn describe(ranges: &Ranges) -> String {
+    CATEGORIES
+        .iter()
+        .zip(ranges.iter())
+        .map(|(name, (min, max))| format!("{} {}..={}", name, min, max))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("./example.txt");
+
+    #[test]
+    fn test_matches_interpreter() {
+        let system = System::new(EXAMPLE);
+        let tree = system.compile().unwrap();
+
+        for (part, ratings) in system.parts.iter().zip(system.part_ratings()) {
+            assert_eq!(tree.is_accepted(&ratings), system.is_accepted(part));
+        }
+
+        // a spread of parts that aren't in the input
+        for i in 0..2000 {
+            let ratings = [(i * 7) % 4000 + 1, (i * 13) % 4000 + 1, (i * 31) % 4000 + 1, (i * 53) % 4000 + 1];
+            let part = HashMap::from([
+                ("x", ratings[0]),
+                ("m", ratings[1]),
+                ("a", ratings[2]),
+                ("s", ratings[3]),
+            ]);
+
+            assert_eq!(tree.is_accepted(&ratings), system.is_accepted(&part), "{:?}", ratings);
+        }
+    }
+
+    #[test]
+    fn test_shared_workflows() {
+        let system = System::new(EXAMPLE);
+        let tree = system.compile().unwrap();
+
+        // one test per conditional rule, since each workflow is only built once
+        let tests = system.workflows
+            .values()
+            .map(|w| w.rules.len() - 1)
+            .sum::<usize>();
+
+        assert_eq!(tree.nodes.len(), tests + 2);
+    }
+
+    #[test]
+    fn test_accepted() {
+        let system = System::new(EXAMPLE);
+        let tree = system.compile().unwrap();
+        let regions = tree.accepted();
+
+        assert_eq!(describe(&regions[0]), "x 1..=1415, m 1..=4000, a 1..=2005, s 1..=1350");
+        assert_eq!(regions.iter().map(combinations).sum::<usize>(), 167409079868000);
+
+        // every part lands in a region if and only if it's accepted
+        for ratings in system.part_ratings() {
+            let inside = regions
+                .iter()
+                .filter(|r| r.iter().zip(ratings).all(|((min, max), v)| *min <= v && v <= *max))
+                .count();
+
+            assert_eq!(inside, tree.is_accepted(&ratings) as usize);
+        }
+    }
+
+    #[test]
+    fn test_catch_all_first() {
+        let system = System::new("in{R,x<5:A}\n\n{x=1,m=2,a=3,s=4}");
+        let tree = system.compile().unwrap();
+
+        assert!(!tree.is_accepted(&[1, 2, 3, 4]));
+        assert!(tree.accepted().is_empty());
+    }
+
+    #[test]
+    fn test_dead_rules() {
+        // x<3:in can't match, so it doesn't go around in circles
+        let system = System::new("in{x<5:A,x<3:in,x>4:R}\n\n{x=1,m=2,a=3,s=4}");
+        let tree = system.compile().unwrap();
+
+        assert_eq!(tree.nodes.len(), 3);
+        assert!(tree.is_accepted(&[4, 1, 1, 1]));
+        assert!(!tree.is_accepted(&[5, 1, 1, 1]));
+    }
+
+    #[test]
+    fn test_falls_through() {
+        let system = System::new("in{x<5:A}\n\n{x=1,m=2,a=3,s=4}");
+
+        assert_eq!(
+            system.compile().err(),
+            Some("error (line 1): some parts don't match any rule in in".to_string())
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let system = System::new("in{x<10:a,A}\n\n{x=1,m=2,a=3,s=4}");
+
+        assert_eq!(
+            system.compile().err(),
+            Some("error (line 1): in goes to a, which doesn't exist".to_string())
+        );
+    }
+}
@@ -1,6 +1,9 @@
-use std::{ time::Instant, fs, collections::HashMap, cmp::Ordering };
+use std::{ env, time::Instant, fs, collections::HashMap, cmp::Ordering };
 use lib::get_part;
 
+mod compile;
+mod validate;
+
 // today's the biggest struct dependencies made so far
 // all with lifetimes
 
@@ -14,10 +17,60 @@ struct Compare<'a> {
 struct Rule<'a> {
     test: Option<Compare<'a>>,
     goto: &'a str,
+    // as it was written, for error messages
+    text: &'a str,
 }
 
 struct Workflow<'a> {
     rules: Vec<Rule<'a>>,
+    // where it is in the input, starting at 1
+    line: usize,
+}
+
+// inclusive (min, max) for x, m, a, s
+type Ranges = [(usize, usize); 4];
+
+const ALL_RANGES: Ranges = [(1, 4000); 4];
+
+// None if it isn't x, m, a or s
+fn category(key: &str) -> Option<usize> {
+    match key {
+        "x" => Some(0),
+        "m" => Some(1),
+        "a" => Some(2),
+        "s" => Some(3),
+        _ => None,
+    }
+}
+
+// (the ranges that pass, the ranges that don't); None if there aren't any
+fn split(ranges: &Ranges, i: usize, cmp: Ordering, num: usize) -> (Option<Ranges>, Option<Ranges>) {
+    let (min, max) = ranges[i];
+
+    // nothing's below 0 or above usize::MAX, and (1, 0) is empty
+    let (pass, fail) = match cmp {
+        Ordering::Less => (num.checked_sub(1).map_or((1, 0), |n| (min, max.min(n))), (min.max(num), max)),
+        _ => (num.checked_add(1).map_or((1, 0), |n| (min.max(n), max)), (min, max.min(num))),
+    };
+
+    let with = |range: (usize, usize)| {
+        if range.0 > range.1 {
+            return None;
+        }
+
+        let mut ranges = *ranges;
+        ranges[i] = range;
+
+        Some(ranges)
+    };
+
+    (with(pass), with(fail))
+}
+
+impl Compare<'_> {
+    fn split(&self, ranges: &Ranges) -> (Option<Ranges>, Option<Ranges>) {
+        split(ranges, category(self.key).expect("validated category"), self.cmp, self.num)
+    }
 }
 
 struct System<'a> {
@@ -36,7 +89,7 @@ impl<'a> System<'a> {
         let trim: &[_] = &['{', '}'];
         let rule_symbols: &[_] = &['<', '>', ':'];
 
-        for workflow in workflow_str.lines() {
+        for (i, workflow) in workflow_str.lines().enumerate() {
             let mut details = workflow.split(trim).take(2);
 
             let name = details.next().expect("name");
@@ -53,6 +106,7 @@ impl<'a> System<'a> {
                         return Rule {
                             goto: rule_parts[0],
                             test: None,
+                            text: rule,
                         };
                     }
                     let cmp = if rule.contains("<") {
@@ -70,12 +124,14 @@ impl<'a> System<'a> {
                     Rule {
                         goto: rule_parts.last().expect("goto"),
                         test: Some(test),
+                        text: rule,
                     }
                 })
                 .collect();
 
             workflows.insert(name, Workflow {
                 rules,
+                line: i + 1,
             });
         }
 
@@ -99,42 +155,49 @@ impl<'a> System<'a> {
         Self { workflows, parts }
     }
 
-    fn get_ratings(&self) -> usize {
-        let mut approved: Vec<&HashMap<&str, usize>> = vec![];
-        
-        for part in self.parts.iter() {
-            // start at 'in'
-            let mut cur = "in";
-
-            while cur != "A" && cur != "R" {
-                for rule in self.workflows.get(cur).expect("don't have cur").rules.iter() {
-                    if let Some(test) = &rule.test {
-                        let val = part.get(test.key).expect("thought we had this key");
-    
-                        if val.cmp(&test.num) == test.cmp {
-                            cur = rule.goto;
-                            break;
-                        }
-                    } else {
-                        // just goto
+    // the interpreter the compiled tree gets checked against
+    #[cfg(test)]
+    fn is_accepted(&self, part: &HashMap<&str, usize>) -> bool {
+        // start at 'in'
+        let mut cur = "in";
+
+        while cur != "A" && cur != "R" {
+            for rule in self.workflows.get(cur).expect("don't have cur").rules.iter() {
+                if let Some(test) = &rule.test {
+                    let val = part.get(test.key).expect("thought we had this key");
+
+                    if val.cmp(&test.num) == test.cmp {
                         cur = rule.goto;
                         break;
                     }
+                } else {
+                    // just goto
+                    cur = rule.goto;
+                    break;
                 }
             }
-            if cur == "A" {
-                approved.push(part);
-            }
         }
 
-        approved.iter().fold(0, |acc, e| {
-            acc + e["x"] + e["m"] + e["a"] + e["s"]
-        })
+        cur == "A"
+    }
+
+    // parts with the categories in x, m, a, s order
+    fn part_ratings(&self) -> Vec<[usize; 4]> {
+        self.parts
+            .iter()
+            .map(|part| [part["x"], part["m"], part["a"], part["s"]])
+            .collect()
     }
 }
 
 fn part_one(system: &System) -> usize {
-    system.get_ratings()
+    let tree = system.compile().expect("workflows to compile");
+
+    system.part_ratings()
+        .iter()
+        .filter(|part| tree.is_accepted(part))
+        .map(|part| part.iter().sum::<usize>())
+        .sum()
 }
 
 fn part_two() -> usize {
@@ -147,6 +210,26 @@ fn main() {
     let contents = fs::read_to_string("./src/input.txt").unwrap();
 
     let system = System::new(&contents);
+    let findings = system.validate();
+
+    for finding in findings.iter() {
+        println!("{}", finding);
+    }
+
+    // the workflows wouldn't compile, and part one would panic
+    if findings.iter().any(|f| f.is_error()) {
+        return;
+    }
+
+    if env::args().any(|arg| arg == "regions") {
+        let tree = system.compile().expect("workflows to compile");
+
+        for region in tree.accepted() {
+            println!("{} ({} combinations)", compile::describe(&region), compile::combinations(&region));
+        }
+
+        return;
+    }
 
     if one {
         let now = Instant::now();
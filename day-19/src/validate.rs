@@ -0,0 +1,414 @@
+// things that are wrong (or just pointless) with the workflows
+
+use std::{ collections::{ HashMap, HashSet }, fmt };
+use crate::{ System, Ranges, ALL_RANGES, category };
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Finding<'a> {
+    // there's no "in" to start from
+    NoStart,
+    // a rule about something other than x, m, a or s
+    UnknownCategory { line: usize, workflow: &'a str, rule: &'a str },
+    // goto a workflow that doesn't exist
+    Missing { line: usize, workflow: &'a str, goto: &'a str },
+    // workflows that can send a part around in circles
+    Cycle { line: usize, path: Vec<&'a str> },
+    // nothing ever goes to it
+    Unreachable { line: usize, workflow: &'a str },
+    // some parts don't match any rule, so they'd never leave
+    FallsThrough { line: usize, workflow: &'a str },
+    // earlier rules in the same workflow already took every part it could match
+    DeadRule { line: usize, workflow: &'a str, rule: &'a str },
+    // every part ends up the same, no matter which rule it takes
+    Constant { line: usize, workflow: &'a str, outcome: &'a str },
+}
+
+impl Finding<'_> {
+    // vs. just a warning
+    pub(crate) fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Finding::NoStart |
+            Finding::UnknownCategory { .. } |
+            Finding::Missing { .. } |
+            Finding::Cycle { .. } |
+            Finding::FallsThrough { .. }
+        )
+    }
+
+    fn line(&self) -> usize {
+        match self {
+            Finding::NoStart => 0,
+            Finding::UnknownCategory { line, .. } |
+            Finding::Missing { line, .. } |
+            Finding::Cycle { line, .. } |
+            Finding::FallsThrough { line, .. } |
+            Finding::Unreachable { line, .. } |
+            Finding::DeadRule { line, .. } |
+            Finding::Constant { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for Finding<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Finding::NoStart => write!(f, "error: there's no 'in' workflow"),
+            Finding::UnknownCategory { line, workflow, rule } =>
+                write!(f, "error (line {}): {} in {} isn't about x, m, a or s", line, rule, workflow),
+            Finding::Missing { line, workflow, goto } =>
+                write!(f, "error (line {}): {} goes to {}, which doesn't exist", line, workflow, goto),
+            Finding::Cycle { line, path } =>
+                write!(f, "error (line {}): workflows loop: {}", line, path.join(" -> ")),
+            Finding::FallsThrough { line, workflow } =>
+                write!(f, "error (line {}): some parts don't match any rule in {}", line, workflow),
+            Finding::Unreachable { line, workflow } =>
+                write!(f, "warning (line {}): nothing goes to {}", line, workflow),
+            Finding::DeadRule { line, workflow, rule } =>
+                write!(f, "warning (line {}): {} can never use {}", line, workflow, rule),
+            Finding::Constant { line, workflow, outcome } =>
+                write!(f, "warning (line {}): {} always ends in {}", line, workflow, outcome),
+        }
+    }
+}
+
+impl<'a> System<'a> {
+    pub(crate) fn validate(&self) -> Vec<Finding<'a>> {
+        let mut findings = vec![];
+
+        // sorted, so the findings come out the same every time
+        let mut names: Vec<&str> = self.workflows.keys().copied().collect();
+        names.sort_by_key(|name| self.workflows[name].line);
+
+        if !self.workflows.contains_key("in") {
+            findings.push(Finding::NoStart);
+        }
+
+        for name in names.iter() {
+            let workflow = &self.workflows[name];
+
+            for rule in workflow.rules.iter() {
+                if rule.test.as_ref().is_some_and(|test| category(test.key).is_none()) {
+                    findings.push(Finding::UnknownCategory { line: workflow.line, workflow: name, rule: rule.text });
+                }
+
+                if !self.is_workflow(rule.goto) {
+                    findings.push(Finding::Missing { line: workflow.line, workflow: name, goto: rule.goto });
+                }
+            }
+        }
+
+        // there's no telling which parts match those rules, so the rest can't be checked
+        if findings.iter().any(|f| matches!(f, Finding::UnknownCategory { .. })) {
+            findings.sort_by_key(|f| f.line());
+
+            return findings;
+        }
+
+        for name in names.iter() {
+            let workflow = &self.workflows[name];
+            let (dead, left) = self.scan_rules(name);
+
+            if left.is_some() {
+                findings.push(Finding::FallsThrough { line: workflow.line, workflow: name });
+            }
+
+            for i in dead {
+                findings.push(Finding::DeadRule { line: workflow.line, workflow: name, rule: workflow.rules[i].text });
+            }
+        }
+
+        findings.extend(self.cycles(&names));
+
+        let reachable = self.reachable();
+
+        for name in names.iter() {
+            if !reachable.contains(name) {
+                findings.push(Finding::Unreachable { line: self.workflows[name].line, workflow: name });
+            }
+        }
+
+        let mut outcomes = HashMap::new();
+
+        for name in names.iter() {
+            let ends = self.outcomes(name, &mut outcomes, &mut HashSet::new());
+
+            if ends.len() == 1 && !ends.contains("?") {
+                let outcome = *ends.iter().next().unwrap();
+
+                findings.push(Finding::Constant { line: self.workflows[name].line, workflow: name, outcome });
+            }
+        }
+
+        // errors first, then by line
+        findings.sort_by_key(|f| (!f.is_error(), f.line()));
+
+        findings
+    }
+
+    fn is_workflow(&self, goto: &str) -> bool {
+        goto == "A" || goto == "R" || self.workflows.contains_key(goto)
+    }
+
+    // indexes of rules where whatever's left after the earlier rules can't match
+    pub(crate) fn dead_rules(&self, name: &str) -> Vec<usize> {
+        self.scan_rules(name).0
+    }
+
+    // (dead rules, what's left after the last rule)
+    fn scan_rules(&self, name: &str) -> (Vec<usize>, Option<Ranges>) {
+        let mut dead = vec![];
+        let mut left = Some(ALL_RANGES);
+
+        for (i, rule) in self.workflows[name].rules.iter().enumerate() {
+            let ranges: Ranges = match left {
+                Some(ranges) => ranges,
+                None => {
+                    dead.push(i);
+                    continue;
+                }
+            };
+
+            match &rule.test {
+                Some(test) => {
+                    let (pass, fail) = test.split(&ranges);
+
+                    if pass.is_none() {
+                        dead.push(i);
+                    }
+
+                    left = fail;
+                }
+                None => left = None,
+            }
+        }
+
+        (dead, left)
+    }
+
+    // the gotos a part can actually take
+    fn live_gotos(&self, name: &str) -> Vec<&'a str> {
+        let dead = self.dead_rules(name);
+
+        self.workflows[name].rules
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !dead.contains(i))
+            .map(|(_, rule)| rule.goto)
+            .collect()
+    }
+
+    fn reachable(&self) -> HashSet<&'a str> {
+        let mut seen = HashSet::new();
+        let mut stack = vec!["in"];
+
+        while let Some(cur) = stack.pop() {
+            if !self.workflows.contains_key(cur) || !seen.insert(cur) {
+                continue;
+            }
+
+            stack.extend(self.live_gotos(cur));
+        }
+
+        seen
+    }
+
+    // each loop once, reported at the workflow that goes back around
+    fn cycles(&self, names: &[&'a str]) -> Vec<Finding<'a>> {
+        let mut findings = vec![];
+        let mut done: HashSet<&str> = HashSet::new();
+
+        for start in names {
+            let mut path = vec![];
+
+            self.find_cycles(start, &mut path, &mut done, &mut findings);
+        }
+
+        findings
+    }
+
+    fn find_cycles(
+        &self,
+        cur: &'a str,
+        path: &mut Vec<&'a str>,
+        done: &mut HashSet<&'a str>,
+        findings: &mut Vec<Finding<'a>>
+    ) {
+        if done.contains(cur) || !self.workflows.contains_key(cur) {
+            return;
+        }
+
+        if let Some(i) = path.iter().position(|p| *p == cur) {
+            let mut looped = path[i..].to_vec();
+            looped.push(cur);
+
+            let last = path.last().expect("came from somewhere");
+
+            findings.push(Finding::Cycle { line: self.workflows[last].line, path: looped });
+
+            return;
+        }
+
+        path.push(cur);
+
+        // a loop through a rule that can't match can't happen
+        for goto in self.live_gotos(cur) {
+            self.find_cycles(goto, path, done, findings);
+        }
+
+        path.pop();
+        done.insert(cur);
+    }
+
+    // every A or R this workflow can end up at, or ? if it might not end
+    fn outcomes(
+        &self,
+        name: &'a str,
+        memo: &mut HashMap<&'a str, HashSet<&'a str>>,
+        visiting: &mut HashSet<&'a str>
+    ) -> HashSet<&'a str> {
+        if name == "A" || name == "R" {
+            return HashSet::from([name]);
+        }
+
+        if let Some(ends) = memo.get(name) {
+            return ends.clone();
+        }
+
+        // missing workflows and loops don't end anywhere we know of
+        if !self.workflows.contains_key(name) || !visiting.insert(name) {
+            return HashSet::from(["?"]);
+        }
+
+        let mut ends = HashSet::new();
+
+        for goto in self.live_gotos(name) {
+            ends.extend(self.outcomes(goto, memo, visiting));
+        }
+
+        visiting.remove(name);
+        memo.insert(name, ends.clone());
+
+        ends
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("./example.txt");
+
+    #[test]
+    fn test_example() {
+        let system = System::new(EXAMPLE);
+        let findings: Vec<String> = system.validate().iter().map(|f| f.to_string()).collect();
+
+        assert_eq!(findings, vec![
+            "warning (line 3): lnx always ends in A",
+            // only because lnx does
+            "warning (line 5): qs always ends in A",
+            "warning (line 10): gd always ends in R",
+        ]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let system = System::new("in{x<10:a,b}
+a{m>5:b,nope}
+b{s>3:a,A}
+c{x<5:R,x<3:A,R}
+
+{x=1,m=2,a=3,s=4}");
+        let findings = system.validate();
+
+        assert_eq!(findings[0], Finding::Missing { line: 2, workflow: "a", goto: "nope" });
+        assert_eq!(findings[1], Finding::Cycle { line: 3, path: vec!["a", "b", "a"] });
+        assert!(findings[0].is_error() && findings[1].is_error());
+
+        assert!(findings.contains(&Finding::Unreachable { line: 4, workflow: "c" }));
+        assert!(findings.contains(&Finding::DeadRule { line: 4, workflow: "c", rule: "x<3:A" }));
+        assert!(findings.contains(&Finding::Constant { line: 4, workflow: "c", outcome: "R" }));
+        assert_eq!(findings.len(), 5);
+    }
+
+    #[test]
+    fn test_unknown_category() {
+        let system = System::new("in{q<10:A,R}\n\n{x=1,m=2,a=3,s=4}");
+        let findings = system.validate();
+
+        assert_eq!(findings, vec![Finding::UnknownCategory { line: 1, workflow: "in", rule: "q<10:A" }]);
+        assert_eq!(findings[0].to_string(), "error (line 1): q<10:A in in isn't about x, m, a or s");
+        assert!(system.compile().is_err());
+    }
+
+    #[test]
+    fn test_below_zero() {
+        // nothing is less than 0, so the rule never matches
+        let system = System::new("in{x<0:R,A}\n\n{x=1,m=2,a=3,s=4}");
+        let findings = system.validate();
+
+        assert!(findings.contains(&Finding::DeadRule { line: 1, workflow: "in", rule: "x<0:R" }));
+        assert!(!findings.iter().any(|f| f.is_error()));
+        assert!(system.compile().unwrap().is_accepted(&[1, 1, 1, 1]));
+    }
+
+    #[test]
+    fn test_no_start() {
+        let system = System::new("a{x<10:A,R}\n\n{x=1,m=2,a=3,s=4}");
+        let findings = system.validate();
+
+        assert_eq!(findings[0], Finding::NoStart);
+        assert_eq!(findings[0].to_string(), "error: there's no 'in' workflow");
+    }
+
+    #[test]
+    fn test_dead_after_catch_all() {
+        // the second rule can't match anything the first one didn't
+        let system = System::new("in{x>0:A,m<5:R,R}\n\n{x=1,m=2,a=3,s=4}");
+
+        assert_eq!(system.dead_rules("in"), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_falls_through() {
+        // x>=5 never leaves in
+        let system = System::new("in{x<5:A}\n\n{x=1,m=2,a=3,s=4}");
+        let findings = system.validate();
+
+        assert_eq!(findings[0], Finding::FallsThrough { line: 1, workflow: "in" });
+        assert!(findings[0].is_error());
+
+        // the last rule is conditional, but catches everything left
+        let system = System::new("in{x<5:A,x>4:R}\n\n{x=1,m=2,a=3,s=4}");
+
+        assert!(!system.validate().iter().any(|f| f.is_error()));
+    }
+
+    #[test]
+    fn test_same_rule_twice() {
+        // only the second one is dead
+        let system = System::new("in{x<5:A,x<5:A,R}\n\n{x=1,m=2,a=3,s=4}");
+        let findings = system.validate();
+
+        assert_eq!(system.dead_rules("in"), vec![1]);
+        assert_eq!(findings, vec![Finding::DeadRule { line: 1, workflow: "in", rule: "x<5:A" }]);
+    }
+
+    #[test]
+    fn test_dead_loop() {
+        // x<3 can never happen after x<5, so it never goes back to in
+        let system = System::new("in{x<5:A,x<3:in,R}\n\n{x=1,m=2,a=3,s=4}");
+        let findings = system.validate();
+
+        assert_eq!(findings, vec![Finding::DeadRule { line: 1, workflow: "in", rule: "x<3:in" }]);
+        assert!(!findings.iter().any(|f| f.is_error()));
+    }
+
+    #[test]
+    fn test_only_reachable_through_dead_rules() {
+        let system = System::new("in{x<5:A,x<3:b,R}\nb{A}\n\n{x=1,m=2,a=3,s=4}");
+
+        assert!(system.validate().contains(&Finding::Unreachable { line: 2, workflow: "b" }));
+    }
+}
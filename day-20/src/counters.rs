@@ -0,0 +1,354 @@
+// part two, without assuming the answer is just multiplying four numbers:
+// each broadcaster output should be its own little circuit; a chain of
+// flip-flops counting presses in binary, and a conjunction (the hub) that
+// fires once the count reaches some number, then resets it to 0
+//
+// broadcaster -> a0
+// %a0 -> a1, hub   <- bit 0 is on in the period
+// %a1 -> a2        <- bit 1 is off, so the hub sends to it instead
+// %a2 -> hub
+// &hub -> a0, a1, inv
+// &inv -> gate     <- one of these for every counter
+// &gate -> rx
+
+use std::{ collections::HashSet, fmt };
+use lib::crt;
+use crate::{ Circuit, Config, ModuleType };
+
+#[derive(Debug)]
+pub(crate) struct Counter {
+    start: usize,
+    // lowest bit first
+    bits: Vec<usize>,
+    hub: usize,
+    // presses between each time it fires
+    pub(crate) period: u64,
+    // it fires on every press that's `phase` mod `period`
+    pub(crate) phase: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct Analysis {
+    gate: String,
+    pub(crate) counters: Vec<Counter>,
+    // first press that sends rx a low pulse
+    pub(crate) first_low: u64,
+    names: Vec<(String, String)>,
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} counters feed {}", self.counters.len(), self.gate)?;
+
+        for (counter, (start, hub)) in self.counters.iter().zip(self.names.iter()) {
+            writeln!(
+                f,
+                "{}: {} bits, hub {}, period {}, phase {}",
+                start,
+                counter.bits.len(),
+                hub,
+                counter.period,
+                counter.phase
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Config {
+    // `circuit` is wherever the button presses are up to (usually nowhere)
+    pub(crate) fn analyse_counters(&self, circuit: &Circuit) -> Result<Analysis, String> {
+        let name = |id: usize| self.stamp.name(id).to_string();
+
+        let senders = &self.nodes[self.output].receivers;
+
+        if senders.len() != 1 {
+            return Err(format!("rx should have one sender, not {}", senders.len()));
+        }
+
+        let gate = senders[0];
+
+        if self.nodes[gate].variant != ModuleType::Conjunction {
+            return Err(format!("{} isn't a conjunction", name(gate)));
+        }
+
+        let gate_inputs = &self.nodes[gate].receivers;
+        let mut seen: HashSet<usize> = HashSet::new();
+        let mut counters = vec![];
+
+        for start in self.nodes[self.broadcaster].destinations.iter() {
+            let region = self.region(*start, gate);
+
+            if let Some(shared) = region.iter().find(|m| seen.contains(m)) {
+                return Err(format!("{} is in more than one subcircuit", name(*shared)));
+            }
+
+            seen.extend(region.iter());
+
+            let counter = self.decode_counter(*start, gate, circuit)?;
+
+            if !region.contains(&counter.hub) {
+                return Err(format!("{} isn't part of {}'s subcircuit", name(counter.hub), name(*start)));
+            }
+
+            counters.push(counter);
+        }
+
+        if counters.len() != gate_inputs.len() {
+            return Err(format!(
+                "{} has {} inputs, but there are {} counters",
+                name(gate),
+                gate_inputs.len(),
+                counters.len()
+            ));
+        }
+
+        let (phase, period) = counters
+            .iter()
+            .try_fold((0, 1), |acc, c| crt(acc, (c.phase as i128, c.period as i128)))
+            .ok_or("counters never line up")?;
+        let (phase, period) = match (u64::try_from(phase), u64::try_from(period)) {
+            (Ok(phase), Ok(period)) => (phase, period),
+            _ => return Err("counters take too many presses to line up".to_string()),
+        };
+
+        // smallest press after where we are now
+        let now = circuit.presses as u64;
+        let mut first_low = phase;
+
+        if first_low <= now {
+            first_low += (now - first_low) / period * period + period;
+        }
+
+        let names = counters.iter().map(|c| (name(c.start), name(c.hub))).collect();
+
+        Ok(Analysis { gate: name(gate), counters, first_low, names })
+    }
+
+    // everything reachable from `start` without going through the gate
+    fn region(&self, start: usize, gate: usize) -> HashSet<usize> {
+        let mut region = HashSet::from([start]);
+        let mut stack = vec![start];
+
+        while let Some(cur) = stack.pop() {
+            for next in self.nodes[cur].destinations.iter() {
+                if *next == gate || *next == self.broadcaster || region.contains(next) {
+                    continue;
+                }
+
+                region.insert(*next);
+                stack.push(*next);
+            }
+        }
+
+        region
+    }
+
+    fn decode_counter(&self, start: usize, gate: usize, circuit: &Circuit) -> Result<Counter, String> {
+        let name = |id: usize| self.stamp.name(id).to_string();
+        let is = |id: usize, variant: ModuleType| self.nodes[id].variant == variant;
+
+        // follow the flip-flops
+        let mut bits = vec![];
+        let mut cur = start;
+
+        loop {
+            if !is(cur, ModuleType::FlipFlop) {
+                return Err(format!("{} isn't a flip-flop", name(cur)));
+            }
+            if bits.contains(&cur) {
+                return Err(format!("{} loops back on itself", name(cur)));
+            }
+
+            bits.push(cur);
+
+            let next: Vec<usize> = self.nodes[cur].destinations
+                .iter()
+                .copied()
+                .filter(|d| is(*d, ModuleType::FlipFlop))
+                .collect();
+
+            match next[..] {
+                [] => break,
+                [next] => cur = next,
+                _ => return Err(format!("{} feeds more than one flip-flop", name(cur))),
+            }
+        }
+
+        // the one conjunction the bits report to
+        let mut hubs: Vec<usize> = bits
+            .iter()
+            .flat_map(|b| self.nodes[*b].destinations.iter().copied())
+            .filter(|d| is(*d, ModuleType::Conjunction))
+            .collect();
+
+        hubs.sort();
+        hubs.dedup();
+
+        let hub = match hubs[..] {
+            [hub] => hub,
+            _ => return Err(format!("{}'s bits feed {} conjunctions", name(start), hubs.len())),
+        };
+
+        let feeds_hub: Vec<bool> = bits
+            .iter()
+            .map(|b| self.nodes[*b].destinations.contains(&hub))
+            .collect();
+
+        // the period is whichever bits are wired to the hub
+        let period: u64 = feeds_hub
+            .iter()
+            .enumerate()
+            .filter(|(_, feeds)| **feeds)
+            .map(|(i, _)| 1 << i)
+            .sum();
+
+        // an even period doesn't reset back to 0
+        if !feeds_hub[0] {
+            return Err(format!("{}'s period {} is even", name(start), period));
+        }
+
+        // the hub sends to bit 0 and every bit that isn't in the period,
+        // which adds just enough to overflow back to 0
+        for (i, bit) in bits.iter().enumerate() {
+            let resets = self.nodes[hub].destinations.contains(bit);
+
+            if resets != (i == 0 || !feeds_hub[i]) {
+                return Err(format!("{} doesn't reset {} properly", name(hub), name(*bit)));
+            }
+        }
+
+        // and the only other thing the hub sends to flips its pulse for the gate
+        let others: Vec<usize> = self.nodes[hub].destinations
+            .iter()
+            .copied()
+            .filter(|d| !bits.contains(d))
+            .collect();
+
+        match others[..] {
+            [inv] if
+                is(inv, ModuleType::Conjunction) &&
+                self.nodes[inv].receivers == vec![hub] &&
+                self.nodes[inv].destinations == vec![gate] => {}
+            _ => return Err(format!("{} doesn't go through an inverter to {}", name(hub), name(gate))),
+        }
+
+        // where the count is now
+        let value: u64 = bits
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| circuit.modules[**b].is_on() == Some(true))
+            .map(|(i, _)| 1 << i)
+            .sum();
+
+        if value >= period {
+            return Err(format!("{} is already past its period", name(start)));
+        }
+
+        let next_fire = circuit.presses as u64 + period - value;
+
+        Ok(Counter { start, bits, hub, period, phase: next_fire % period })
+    }
+}
+
+// cargo run -- counters: what each counter looks like, or why it isn't one
+pub(crate) fn run(config: &Config) {
+    match config.analyse_counters(&config.circuit()) {
+        Ok(analysis) => {
+            print!("{}", analysis);
+            println!("first low pulse to rx: press {}", analysis.first_low);
+        }
+        Err(err) => println!("doesn't look like counters: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pulse;
+
+    const COUNTERS: &str = include_str!("./example-2.txt");
+
+    // keep pressing until rx gets a low pulse
+    fn brute_force(config: &Config, circuit: &mut Circuit) -> u64 {
+        loop {
+            let mut low = false;
+
+            circuit.press(|sent| {
+                if sent.to == config.output && sent.pulse == Pulse::Low {
+                    low = true;
+                }
+            });
+
+            if low {
+                return circuit.presses as u64;
+            }
+        }
+    }
+
+    #[test]
+    fn test_counters() {
+        let config = Config::new(COUNTERS);
+        let analysis = config.analyse_counters(&config.circuit()).unwrap();
+        let periods: Vec<u64> = analysis.counters.iter().map(|c| c.period).collect();
+
+        assert_eq!(periods, vec![11, 13, 9]);
+        assert!(analysis.counters.iter().all(|c| c.phase == 0));
+        assert_eq!(analysis.gate, "gate");
+        assert_eq!(
+            analysis.to_string(),
+            "3 counters feed gate
+a0: 4 bits, hub huba, period 11, phase 0
+b0: 4 bits, hub hubb, period 13, phase 0
+c0: 4 bits, hub hubc, period 9, phase 0
+"
+        );
+        assert_eq!(analysis.first_low, 11 * 13 * 9);
+
+        assert_eq!(analysis.first_low, brute_force(&config, &mut config.circuit()));
+    }
+
+    #[test]
+    fn test_counters_part_way() {
+        let config = Config::new(COUNTERS);
+        let mut circuit = config.circuit();
+
+        // start part way; they still fire on multiples of their periods
+        for _ in 0..100 {
+            circuit.press(|_| {});
+        }
+
+        let analysis = config.analyse_counters(&circuit).unwrap();
+        let phases: Vec<u64> = analysis.counters.iter().map(|c| c.phase).collect();
+
+        assert_eq!(phases, vec![0, 0, 0]);
+        assert_eq!(analysis.first_low, 1287);
+        assert_eq!(analysis.first_low, brute_force(&config, &mut circuit));
+
+        // and again, past the first one
+        let analysis = config.analyse_counters(&circuit).unwrap();
+
+        assert_eq!(analysis.first_low, 2574);
+    }
+
+    #[test]
+    fn test_not_counters() {
+        let config = Config::new(include_str!("./example.txt"));
+        let err = config.analyse_counters(&config.circuit()).unwrap_err();
+
+        assert_eq!(err, "a's bits feed 2 conjunctions");
+
+        // rx with two senders
+        let config = Config::new("broadcaster -> a\n%a -> rx, b\n%b -> rx");
+        let err = config.analyse_counters(&config.circuit()).unwrap_err();
+
+        assert_eq!(err, "rx should have one sender, not 2");
+
+        // even period
+        let config = Config::new(&COUNTERS.replace("%a0 -> a1, huba", "%a0 -> a1"));
+        let err = config.analyse_counters(&config.circuit()).unwrap_err();
+
+        assert_eq!(err, "a0's period 10 is even");
+    }
+}
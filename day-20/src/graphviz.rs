@@ -0,0 +1,125 @@
+// draw the circuit instead of doing it by hand
+//
+// cargo run -- dot > circuit.dot      (just the wiring)
+// cargo run -- dot 1000 > circuit.dot (flip-flops coloured by state after 1000 presses)
+// dot -Tsvg circuit.dot > circuit.svg
+
+use crate::{ Circuit, Config, ModuleType };
+
+impl Config {
+    // `circuit` colours the flip-flops green (on) or grey (off)
+    pub(crate) fn to_dot(&self, circuit: Option<&Circuit>) -> String {
+        let mut lines = vec!["digraph circuit {".to_string()];
+
+        for (id, node) in self.nodes.iter().enumerate() {
+            let shape = match node.variant {
+                ModuleType::FlipFlop => "box",
+                ModuleType::Conjunction => "invhouse",
+                ModuleType::Broadcast => "doublecircle",
+                ModuleType::Sink => "doubleoctagon",
+                ModuleType::Custom(_) => "hexagon",
+            };
+
+            let fill = if id == self.broadcaster {
+                Some("lightblue")
+            } else if id == self.output {
+                Some("gold")
+            } else {
+                circuit
+                    .and_then(|c| c.modules[id].is_on())
+                    .map(|on| if on { "palegreen" } else { "lightgrey" })
+            };
+
+            let style = match fill {
+                Some(fill) => format!(", style=filled, fillcolor={}", fill),
+                None => String::new(),
+            };
+
+            lines.push(format!("  \"{}\" [shape={}{}];", self.stamp.name(id), shape, style));
+        }
+
+        for (id, node) in self.nodes.iter().enumerate() {
+            for dest in node.destinations.iter() {
+                lines.push(format!(
+                    "  \"{}\" -> \"{}\";",
+                    self.stamp.name(id),
+                    self.stamp.name(*dest)
+                ));
+            }
+        }
+
+        lines.push("}".to_string());
+
+        lines.join("\n")
+    }
+}
+
+// `args` is everything after "dot"
+pub(crate) fn run(config: &Config, args: &[String]) {
+    let presses: Option<usize> = args.first().map(|n| n.parse().expect("number of presses"));
+
+    match presses {
+        Some(presses) => {
+            let mut circuit = config.circuit();
+
+            for _ in 0..presses {
+                circuit.press(|_| {});
+            }
+
+            println!("{}", config.to_dot(Some(&circuit)));
+        }
+        None => println!("{}", config.to_dot(None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("./example.txt");
+
+    #[test]
+    fn test_dot() {
+        let config = Config::new(EXAMPLE);
+
+        assert_eq!(
+            config.to_dot(None),
+            r#"digraph circuit {
+  "broadcaster" [shape=doublecircle, style=filled, fillcolor=lightblue];
+  "a" [shape=box];
+  "inv" [shape=invhouse];
+  "con" [shape=invhouse];
+  "b" [shape=box];
+  "output" [shape=doubleoctagon, style=filled, fillcolor=gold];
+  "broadcaster" -> "a";
+  "a" -> "inv";
+  "a" -> "con";
+  "inv" -> "b";
+  "con" -> "output";
+  "b" -> "con";
+}"#
+        );
+    }
+
+    #[test]
+    fn test_dot_overlay() {
+        let config = Config::new(EXAMPLE);
+        let mut circuit = config.circuit();
+
+        // a turns on, then b turns on
+        circuit.press(|_| {});
+
+        let dot = config.to_dot(Some(&circuit));
+
+        assert!(dot.contains(r#""a" [shape=box, style=filled, fillcolor=palegreen];"#));
+        assert!(dot.contains(r#""b" [shape=box, style=filled, fillcolor=palegreen];"#));
+        // conjunctions aren't on or off
+        assert!(dot.contains(r#""con" [shape=invhouse];"#));
+
+        circuit.press(|_| {});
+
+        let dot = config.to_dot(Some(&circuit));
+
+        assert!(dot.contains(r#""a" [shape=box, style=filled, fillcolor=lightgrey];"#));
+    }
+}
@@ -1,6 +1,11 @@
 use lib::get_part;
-use std::{ collections::HashMap, fs, time::Instant, usize, vec };
+use std::{ collections::{ HashMap, VecDeque }, env, fmt, fs, time::Instant, vec };
 
+mod counters;
+mod graphviz;
+mod trace;
+
+#[derive(Debug)]
 struct Stamp {
     map: HashMap<String, usize>,
     next: usize,
@@ -26,355 +31,311 @@ impl Stamp {
             value
         }
     }
+
+    // going back the other way
+    fn name(&self, value: usize) -> &str {
+        if value == BUTTON {
+            return "button";
+        }
+
+        self.map
+            .iter()
+            .find(|(_, v)| **v == value)
+            .map(|(k, _)| k.as_str())
+            .unwrap_or("?")
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum ModuleType {
-    FlipFlop(bool),
+    FlipFlop,
     Conjunction,
     Broadcast,
+    // anything that isn't defined, like "rx" or "output"
+    Sink,
+    // whatever symbol it was given
+    Custom(char),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Pulse {
     High,
     Low,
-    None,
+}
+
+// the button isn't a module, but it does send the first pulse
+const BUTTON: usize = usize::MAX;
+
+// anything that can sit in the circuit; `receive` returns the pulse
+// (if any) that gets sent to every destination
+trait Module: fmt::Debug {
+    // told about every module that sends to it, before anything is pressed
+    fn connect(&mut self, _input: usize) {}
+
+    fn receive(&mut self, from: usize, pulse: Pulse) -> Option<Pulse>;
+
+    // only means something for modules that can be switched on
+    fn is_on(&self) -> Option<bool> {
+        None
+    }
+
+    // TIL: can't derive Clone for Box<dyn Trait>
+    fn clone_box(&self) -> Box<dyn Module>;
+}
+
+impl Clone for Box<dyn Module> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FlipFlop {
+    on: bool,
+}
+
+impl Module for FlipFlop {
+    // if a flip-flop module receives a low pulse, it flips
+    // between on and off. If it was off, it turns on and
+    // sends a high pulse. If it was on, it turns off and
+    // sends a low pulse.
+    fn receive(&mut self, _from: usize, pulse: Pulse) -> Option<Pulse> {
+        if pulse == Pulse::High {
+            return None;
+        }
+
+        self.on = !self.on;
+
+        Some(if self.on { Pulse::High } else { Pulse::Low })
+    }
+
+    fn is_on(&self) -> Option<bool> {
+        Some(self.on)
+    }
+
+    fn clone_box(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Conjunction {
+    // last pulse from each input
+    memory: HashMap<usize, Pulse>,
+}
+
+impl Module for Conjunction {
+    // they initially default to remembering a low pulse for each input
+    fn connect(&mut self, input: usize) {
+        self.memory.insert(input, Pulse::Low);
+    }
+
+    // if it remembers high pulses for all inputs,
+    // it sends a low pulse; otherwise, it sends a high pulse.
+    fn receive(&mut self, from: usize, pulse: Pulse) -> Option<Pulse> {
+        self.memory.insert(from, pulse);
+
+        if self.memory.values().all(|p| *p == Pulse::High) {
+            Some(Pulse::Low)
+        } else {
+            Some(Pulse::High)
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Broadcast;
+
+impl Module for Broadcast {
+    fn receive(&mut self, _from: usize, pulse: Pulse) -> Option<Pulse> {
+        Some(pulse)
+    }
+
+    fn clone_box(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
 }
 
 #[derive(Debug, Clone)]
-struct Module {
+struct Sink;
+
+impl Module for Sink {
+    fn receive(&mut self, _from: usize, _pulse: Pulse) -> Option<Pulse> {
+        None
+    }
+
+    fn clone_box(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+}
+
+// how a module is wired up
+#[derive(Debug, Clone)]
+struct Node {
     receivers: Vec<usize>,
     destinations: Vec<usize>,
     variant: ModuleType,
-    last_pulse: Pulse,
+}
+
+// one pulse going from one module to another
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Sent {
+    from: usize,
+    pulse: Pulse,
+    to: usize,
 }
 
 #[derive(Debug)]
 struct Config {
-    modules: Vec<Module>,
+    stamp: Stamp,
+    nodes: Vec<Node>,
+    // every module as it is before the button is pushed
+    modules: Vec<Box<dyn Module>>,
     output: usize,
     broadcaster: usize,
 }
 
+// the modules' state while pushing the button
+struct Circuit<'a> {
+    config: &'a Config,
+    modules: Vec<Box<dyn Module>>,
+    presses: usize,
+}
+
 impl Config {
     fn new(contents: &str) -> Self {
-        // convert str to u8
-        let stamp = &mut Stamp::new();
-
-        // rx never turns into a module, so we don't want the usize messing with the vec indexing
-        stamp.map.insert("rx".to_string(), usize::MAX);
-
-        let mut module_map = HashMap::new();
-        let mut keys_destinations = HashMap::new();
+        Self::with_modules(contents, |_| None)
+    }
 
-        // still not sure what this type is
-        let symbols: &[_] = &['%', '&'];
+    // `custom` gets first dibs on every symbol in front of a module name,
+    // so new kinds of modules don't need to touch the parsing
+    fn with_modules(
+        contents: &str,
+        custom: impl Fn(char) -> Option<Box<dyn Module>>
+    ) -> Self {
+        // convert str to usize
+        let mut stamp = Stamp::new();
+        let mut defined: HashMap<usize, (ModuleType, Box<dyn Module>)> = HashMap::new();
+        let mut keys_destinations = vec![];
 
         contents.lines().for_each(|line| {
             let (input, output) = line.split_once(" -> ").expect("an arrow");
 
+            let symbol = input.chars().next().expect("a module name");
+            let name = input.trim_start_matches(|c: char| !c.is_alphanumeric());
+            let key = stamp.stamp(name);
+
             let destinations: Vec<_> = output
                 .split(", ")
                 .map(|x| stamp.stamp(x))
                 .collect();
-            // receivers will be unknown until we loop again afterward
-            let receivers = vec![];
-            let last_pulse = Pulse::None;
-
-            let key = stamp.stamp(input.trim_matches(symbols));
-
-            keys_destinations.insert(key, destinations.clone());
-
-            match input {
-                "broadcaster" => {
-                    module_map.insert(key, Module {
-                        destinations,
-                        receivers,
-                        last_pulse: Pulse::Low,
-                        variant: ModuleType::Broadcast,
-                    });
-                }
-                n if n.starts_with("%") => {
-                    module_map.insert(key, Module {
-                        destinations,
-                        receivers,
-                        last_pulse,
-                        variant: ModuleType::FlipFlop(false),
-                    });
-                }
-                n if n.starts_with("&") => {
-                    module_map.insert(key, Module {
-                        destinations,
-                        receivers,
-                        last_pulse,
-                        variant: ModuleType::Conjunction,
-                    });
-                }
-                _ => unreachable!(),
-            }
+
+            keys_destinations.push((key, destinations));
+
+            let module: (ModuleType, Box<dyn Module>) = match custom(symbol) {
+                Some(module) => (ModuleType::Custom(symbol), module),
+                None => match symbol {
+                    '%' => (ModuleType::FlipFlop, Box::new(FlipFlop { on: false })),
+                    '&' => (ModuleType::Conjunction, Box::new(Conjunction::default())),
+                    _ if name == "broadcaster" => (ModuleType::Broadcast, Box::new(Broadcast)),
+                    _ => panic!("what kind of module is {}?", input),
+                },
+            };
+
+            defined.insert(key, module);
         });
 
-        // this is a pain
-        for (key, destinations) in keys_destinations {
-            for dest in destinations {
-                if let Some(module) = module_map.get_mut(&dest) {
-                    module.receivers.push(key);
-                }
-            }
+        // example data has "output" instead of "rx"; rx might not exist,
+        // but stamp it anyway so it's a sink like "output"
+        let output = match stamp.get("output") {
+            Some(output) => output,
+            None => stamp.stamp("rx"),
+        };
+
+        // everything that got stamped gets a module, even if it's just a sink
+        let mut nodes = vec![];
+        let mut modules = vec![];
+
+        for i in 0..stamp.next {
+            let (variant, module) = defined
+                .remove(&i)
+                .unwrap_or((ModuleType::Sink, Box::new(Sink)));
+
+            nodes.push(Node { receivers: vec![], destinations: vec![], variant });
+            modules.push(module);
         }
 
-        // converts the hashmap to a vector
-        let modules = (0..)
-            .map_while(|i| {
-                if let Some(module) = module_map.get(&i) {
-                    // crazy to have to clone this
-                    Some(module.clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
+        for (key, destinations) in keys_destinations {
+            for dest in destinations.iter() {
+                nodes[*dest].receivers.push(key);
+                modules[*dest].connect(key);
+            }
 
-        // rx might not exist
-        let unknown = stamp.stamp("UNKNOWN");
-        let output = *stamp.map
-            .get("output")
-            .or(stamp.map.get("rx"))
-            .unwrap_or(&unknown);
+            nodes[key].destinations = destinations;
+        }
 
         Self {
+            broadcaster: stamp.get("broadcaster").expect("broadcaster"),
+            stamp,
+            nodes,
             modules,
             output,
-            broadcaster: stamp.get("broadcaster").expect("broadcaster"),
         }
     }
 
-    fn push_button(&self, time: usize) -> usize {
-        let mut modules = self.modules.clone();
-
-        let broadcaster = &modules[self.broadcaster];
+    fn circuit(&self) -> Circuit<'_> {
+        Circuit {
+            config: self,
+            modules: self.modules.clone(),
+            presses: 0,
+        }
+    }
 
-        // button sends low to broadcaster, so low starts at `time`, plus all the
-        // pulses broadcaster *will* send
-        let mut low = time + broadcaster.destinations.len() * time;
+    fn push_button(&self, time: usize) -> usize {
+        let mut circuit = self.circuit();
+        let mut low = 0;
         let mut high = 0;
-        // broadcaster always sends low to destinations
-        let broadcasts = broadcaster.destinations
-            .iter()
-            .map(|&d| { (Pulse::Low, d) })
-            .collect::<Vec<_>>();
 
         for _ in 0..time {
-            let mut next_destinations = broadcasts.clone();
-
-            // go through destinations
-            while next_destinations.len() > 0 {
-                let mut next: Vec<(Pulse, usize)> = vec![];
-
-                for (last_pulse, dest) in next_destinations.iter() {
-                    if *dest == self.output {
-                        // example data has "output" as a destination
-                        continue;
-                    }
-
-                    let module = &modules[*dest];
-
-                    match (last_pulse, &module.variant) {
-                        (Pulse::Low, ModuleType::FlipFlop(onoff)) => {
-                            // if a flip-flop module receives a low pulse, it flips
-                            // between on and off. If it was off, it turns on and
-                            // sends a high pulse. If it was on, it turns off and
-                            // sends a low pulse.
-                            let toggled = !onoff;
-
-                            // ! TIL: I can get_mut when I need it, though this seems incredibly wasteful
-
-                            let module = modules.get_mut(*dest).unwrap();
-                            module.variant = ModuleType::FlipFlop(toggled);
-                            module.last_pulse = if toggled {
-                                Pulse::High
-                            } else {
-                                Pulse::Low
-                            };
-
-                            if toggled {
-                                high += module.destinations.len();
-                                next.extend(
-                                    module.destinations
-                                        .iter()
-                                        .map(|&d| { (Pulse::High, d) })
-                                );
-                            } else {
-                                low += module.destinations.len();
-                                next.extend(
-                                    module.destinations
-                                        .iter()
-                                        .map(|&d| { (Pulse::Low, d) })
-                                );
-                            }
-                        }
-                        (_, ModuleType::Conjunction) => {
-                            // they initially default to remembering a low pulse for each input
-                            // Then, if it remembers high pulses for all inputs,
-                            // it sends a low pulse; otherwise, it sends a high pulse.
-                            // look up receivers
-                            let all_high = &module.receivers.iter().all(|&r| {
-                                // None is equivalent to Low
-                                &modules[r].last_pulse == &Pulse::High
-                            });
-
-                            let module = modules.get_mut(*dest).unwrap();
-
-                            if *all_high {
-                                low += module.destinations.len();
-                                module.last_pulse = Pulse::Low;
-                                next.extend(
-                                    module.destinations
-                                        .iter()
-                                        .map(|&d| { (Pulse::Low, d) })
-                                );
-                            } else {
-                                high += module.destinations.len();
-                                module.last_pulse = Pulse::High;
-                                next.extend(
-                                    module.destinations
-                                        .iter()
-                                        .map(|&d| { (Pulse::High, d) })
-                                );
-                            }
-                        }
-                        _ => {}
-                    }
+            circuit.press(|sent| {
+                match sent.pulse {
+                    Pulse::Low => low += 1,
+                    Pulse::High => high += 1,
                 }
-
-                // is mem swap faster?
-                std::mem::swap(&mut next_destinations, &mut next);
-            }
+            });
         }
 
         low * high
     }
+}
 
-    fn get_cycles_for_rx(&self) -> Vec<i32> {
-        let mut modules = self.modules.clone();
-
-        let broadcaster = &modules[self.broadcaster];
+impl Circuit<'_> {
+    // button sends low to broadcaster, and every pulse gets handled in the
+    // order it was sent; `on_pulse` sees each one as it's delivered
+    fn press(&mut self, mut on_pulse: impl FnMut(&Sent)) {
+        let mut queue = VecDeque::from([Sent {
+            from: BUTTON,
+            pulse: Pulse::Low,
+            to: self.config.broadcaster,
+        }]);
 
-        // broadcaster always sends low to destinations
-        let broadcasts = broadcaster.destinations
-            .iter()
-            .map(|&d| { (Pulse::Low, d) })
-            .collect::<Vec<_>>();
+        self.presses += 1;
 
-        // find the sender for rx
-        let sender = modules
-            .iter()
-            .find(|x| { x.destinations.contains(&self.output) })
-            .unwrap();
+        while let Some(sent) = queue.pop_front() {
+            on_pulse(&sent);
 
-        // find the senders for that (jq) (4 of them, and they're all conjunction)
-        let mut receivers: HashMap<usize, i32> = HashMap::from_iter(
-            sender.receivers.iter().map(|&x| (x, 0))
-        );
+            let next = self.modules[sent.to].receive(sent.from, sent.pulse);
 
-        'madness: for i in 1..1_000_000 {
-            let mut next_destinations = broadcasts.clone();
-
-            // go through destinations
-            while next_destinations.len() > 0 {
-                let mut next: Vec<(Pulse, usize)> = vec![];
-
-                for (last_pulse, dest) in next_destinations.iter() {
-                    if *dest == self.output {
-                        // example data has "output" as a destination
-                        continue;
-                    }
-
-                    let module = &modules[*dest];
-
-                    match (last_pulse, &module.variant) {
-                        (Pulse::Low, ModuleType::FlipFlop(onoff)) => {
-                            let toggled = !onoff;
-
-                            let module = modules.get_mut(*dest).unwrap();
-                            module.variant = ModuleType::FlipFlop(toggled);
-                            module.last_pulse = if toggled {
-                                Pulse::High
-                            } else {
-                                Pulse::Low
-                            };
-
-                            if toggled {
-                                next.extend(
-                                    module.destinations
-                                        .iter()
-                                        .map(|&d| { (Pulse::High, d) })
-                                );
-                            } else {
-                                next.extend(
-                                    module.destinations
-                                        .iter()
-                                        .map(|&d| { (Pulse::Low, d) })
-                                );
-                            }
-                        }
-                        (_, ModuleType::Conjunction) => {
-                            let all_high = module.receivers
-                                .iter()
-                                .all(|&r| {
-                                    modules[r].last_pulse == Pulse::High
-                                });
-
-                            let module = modules.get_mut(*dest).unwrap();
-
-                            if all_high {
-                                // sender needs to send low pulse; so receivers all need to send high
-                                module.last_pulse = Pulse::Low;
-                                next.extend(
-                                    module.destinations
-                                        .iter()
-                                        .map(|&d| { (Pulse::Low, d) })
-                                );
-                            } else {
-                                // all receivers need to send high pulses to get the sender to send a low
-                                if receivers.contains_key(dest) {
-                                    receivers.entry(*dest).and_modify(|x| {
-                                        if *x == 0 {
-                                            *x = i;
-                                        }
-                                    });
-
-                                    if
-                                        receivers
-                                            .iter()
-                                            .all(|(_, v)| { *v != 0 })
-                                    {
-                                        break 'madness;
-                                    }
-                                }
-
-                                module.last_pulse = Pulse::High;
-                                next.extend(
-                                    module.destinations
-                                        .iter()
-                                        .map(|&d| { (Pulse::High, d) })
-                                );
-                            }
-                        }
-                        _ => {}
-                    }
+            if let Some(pulse) = next {
+                for dest in self.config.nodes[sent.to].destinations.iter() {
+                    queue.push_back(Sent { from: sent.to, pulse, to: *dest });
                 }
-
-                // is mem swap faster?
-                std::mem::swap(&mut next_destinations, &mut next);
             }
         }
-
-        receivers
-            .iter()
-            .map(|(_, v)| { *v })
-            .collect::<Vec<_>>()
     }
 }
 
@@ -382,13 +343,11 @@ fn part_one(config: &Config) -> usize {
     config.push_button(1000)
 }
 
-fn part_two(config: &Config) -> usize {
-    // wow, blown away that this worked
+// fails if the input isn't wired up as counters; `cargo run -- counters` says why
+fn part_two(config: &Config) -> Result<usize, String> {
     config
-        .get_cycles_for_rx()
-        .iter()
-        .map(|x| *x as usize)
-        .product()
+        .analyse_counters(&config.circuit())
+        .map(|analysis| analysis.first_low as usize)
 }
 
 fn main() {
@@ -398,6 +357,30 @@ fn main() {
 
     let config = Config::new(&contents);
 
+    // cargo run -- trace/step <presses> [modules...] [low|high]
+    // cargo run -- dot [presses]
+    // cargo run -- counters
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(|a| a.as_str()) {
+        Some(mode @ ("trace" | "step")) => {
+            trace::run(&config, mode == "step", &args[1..]);
+
+            return;
+        }
+        Some("dot") => {
+            graphviz::run(&config, &args[1..]);
+
+            return;
+        }
+        Some("counters") => {
+            counters::run(&config);
+
+            return;
+        }
+        _ => {}
+    }
+
     if one {
         let now = Instant::now();
         let ans = part_one(&config);
@@ -406,8 +389,11 @@ fn main() {
 
     if two {
         let now = Instant::now();
-        let ans = part_two(&config);
-        println!("Part two: {:?} {:?}", ans, now.elapsed());
+
+        match part_two(&config) {
+            Ok(ans) => println!("Part two: {:?} {:?}", ans, now.elapsed()),
+            Err(err) => println!("Part two: doesn't look like counters: {}", err),
+        }
     }
 
     println!("Time: {:?}", start.elapsed())
@@ -450,6 +436,83 @@ mod tests {
         assert_eq!(config.push_button(1), 8 * 4);
     }
 
+    #[test]
+    fn test_fifo() {
+        let config = Config::new(OTHER);
+        let mut circuit = config.circuit();
+        let mut order = vec![];
+
+        circuit.press(|sent| order.push((sent.from, sent.to)));
+
+        let id = |name| config.stamp.get(name).unwrap();
+
+        // a, b, c all hear from the broadcaster before anything else happens
+        assert_eq!(order[0], (BUTTON, id("broadcaster")));
+        assert_eq!(order[1], (id("broadcaster"), id("a")));
+        assert_eq!(order[2], (id("broadcaster"), id("b")));
+        assert_eq!(order[3], (id("broadcaster"), id("c")));
+        assert_eq!(order[4], (id("a"), id("b")));
+        assert_eq!(order.len(), 12);
+    }
+
+    #[test]
+    fn test_conjunction_memory() {
+        // m gets two pulses in one press, then sends two to c
+        let config = Config::new(
+            "broadcaster -> a, b
+%a -> m
+%b -> m
+&m -> c
+&c -> output"
+        );
+        let mut circuit = config.circuit();
+        let mut to_output = vec![];
+        let output = config.output;
+
+        circuit.press(|sent| {
+            if sent.to == output {
+                to_output.push(sent.pulse);
+            }
+        });
+
+        // c remembers m's high, then m's low
+        assert_eq!(to_output, vec![Pulse::Low, Pulse::High]);
+    }
+
+    // sends the opposite of what it gets
+    #[derive(Debug, Clone)]
+    struct Not;
+
+    impl Module for Not {
+        fn receive(&mut self, _from: usize, pulse: Pulse) -> Option<Pulse> {
+            match pulse {
+                Pulse::High => Some(Pulse::Low),
+                Pulse::Low => Some(Pulse::High),
+            }
+        }
+
+        fn clone_box(&self) -> Box<dyn Module> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn test_custom_module() {
+        let config = Config::with_modules("broadcaster -> n\n!n -> output", |symbol| {
+            match symbol {
+                '!' => Some(Box::new(Not)),
+                _ => None,
+            }
+        });
+        let mut circuit = config.circuit();
+        let mut pulses = vec![];
+
+        circuit.press(|sent| pulses.push(sent.pulse));
+
+        assert_eq!(config.nodes[config.stamp.get("n").unwrap()].variant, ModuleType::Custom('!'));
+        assert_eq!(pulses, vec![Pulse::Low, Pulse::Low, Pulse::High]);
+    }
+
     #[test]
     fn test_alt_one_thousand() {
         let config = Config::new(OTHER);
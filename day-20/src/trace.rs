@@ -0,0 +1,220 @@
+// see every pulse that happens while pushing the button
+//
+// cargo run -- trace 3 a inv     (every pulse to or from a or inv, for 3 presses)
+// cargo run -- trace 1000 rx low (just the low pulses to or from rx)
+// cargo run -- step 2            (enter: next pulse, p: rest of press, c: all, q: quit)
+
+use std::io::{ BufRead, Write };
+use crate::{ Config, Pulse };
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Event {
+    pub(crate) press: usize,
+    // order within the press, starting at 0
+    pub(crate) seq: usize,
+    pub(crate) from: usize,
+    pub(crate) pulse: Pulse,
+    pub(crate) to: usize,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Filter {
+    // empty means every module
+    modules: Vec<usize>,
+    pulse: Option<Pulse>,
+}
+
+impl Filter {
+    // module names, plus "low" or "high"
+    pub(crate) fn new(config: &Config, args: &[String]) -> Self {
+        let mut filter = Self::default();
+
+        for arg in args {
+            match arg.as_str() {
+                "low" => filter.pulse = Some(Pulse::Low),
+                "high" => filter.pulse = Some(Pulse::High),
+                name => {
+                    let id = config.stamp.get(name).unwrap_or_else(|| {
+                        panic!("no module called {}", name)
+                    });
+
+                    filter.modules.push(id);
+                }
+            }
+        }
+
+        filter
+    }
+
+    fn matches(&self, event: &Event) -> bool {
+        let module = self.modules.is_empty() ||
+            self.modules.contains(&event.from) ||
+            self.modules.contains(&event.to);
+
+        module && (self.pulse.is_none() || self.pulse == Some(event.pulse))
+    }
+}
+
+pub(crate) struct Trace<'a> {
+    config: &'a Config,
+    pub(crate) events: Vec<Event>,
+}
+
+impl Config {
+    pub(crate) fn trace(&self, presses: usize) -> Trace<'_> {
+        let mut circuit = self.circuit();
+        let mut events = vec![];
+
+        for press in 1..=presses {
+            let mut seq = 0;
+
+            circuit.press(|sent| {
+                events.push(Event { press, seq, from: sent.from, pulse: sent.pulse, to: sent.to });
+                seq += 1;
+            });
+        }
+
+        Trace { config: self, events }
+    }
+}
+
+impl Trace<'_> {
+    pub(crate) fn filtered<'a>(&'a self, filter: &'a Filter) -> impl Iterator<Item = &'a Event> {
+        self.events.iter().filter(move |e| filter.matches(e))
+    }
+
+    // like the puzzle: "press 1 #0: button -low-> broadcaster"
+    pub(crate) fn describe(&self, event: &Event) -> String {
+        let pulse = match event.pulse {
+            Pulse::Low => "low",
+            Pulse::High => "high",
+        };
+
+        format!(
+            "press {} #{}: {} -{}-> {}",
+            event.press,
+            event.seq,
+            self.config.stamp.name(event.from),
+            pulse,
+            self.config.stamp.name(event.to)
+        )
+    }
+
+    // replay the trace one pulse at a time, driven by `input`
+    pub(crate) fn step(
+        &self,
+        filter: &Filter,
+        input: impl BufRead,
+        mut output: impl Write
+    ) -> std::io::Result<()> {
+        let mut events = self.filtered(filter).peekable();
+        let mut lines = input.lines();
+
+        while let Some(event) = events.next() {
+            writeln!(output, "{}", self.describe(event))?;
+
+            let command = match lines.next() {
+                Some(line) => line?,
+                // ran out of input, so just stop
+                None => return Ok(()),
+            };
+
+            match command.trim() {
+                "q" => return Ok(()),
+                "c" => {
+                    for event in events.by_ref() {
+                        writeln!(output, "{}", self.describe(event))?;
+                    }
+                }
+                "p" => {
+                    while let Some(next) = events.next_if(|e| e.press == event.press) {
+                        writeln!(output, "{}", self.describe(next))?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// `args` is everything after "trace" or "step"
+pub(crate) fn run(config: &Config, step: bool, args: &[String]) {
+    let presses = args
+        .first()
+        .map(|n| n.parse().expect("number of presses"))
+        .unwrap_or(1);
+    let filter = Filter::new(config, args.get(1..).unwrap_or(&[]));
+    let trace = config.trace(presses);
+
+    if step {
+        let stdin = std::io::stdin();
+
+        trace.step(&filter, stdin.lock(), std::io::stdout()).expect("stdin/stdout");
+    } else {
+        for event in trace.filtered(&filter) {
+            println!("{}", trace.describe(event));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("./example.txt");
+
+    #[test]
+    fn test_trace() {
+        let config = Config::new(EXAMPLE);
+        let trace = config.trace(2);
+
+        // puzzle says 4 low and 4 high on the first press, then 4 + 2 on the second
+        assert_eq!(trace.events.iter().filter(|e| e.press == 1).count(), 8);
+        assert_eq!(trace.events.iter().filter(|e| e.press == 2).count(), 6);
+
+        assert_eq!(trace.describe(&trace.events[0]), "press 1 #0: button -low-> broadcaster");
+        assert_eq!(trace.describe(&trace.events[7]), "press 1 #7: con -low-> output");
+        assert_eq!(trace.describe(&trace.events[8]), "press 2 #0: button -low-> broadcaster");
+    }
+
+    #[test]
+    fn test_filter() {
+        let config = Config::new(EXAMPLE);
+        let trace = config.trace(4);
+
+        let args = vec!["output".to_string()];
+        let filter = Filter::new(&config, &args);
+
+        assert!(trace.filtered(&filter).all(|e| e.to == config.output));
+        // high then low, high, high then low, high
+        assert_eq!(trace.filtered(&filter).count(), 6);
+
+        let args = vec!["output".to_string(), "low".to_string()];
+        let filter = Filter::new(&config, &args);
+        let lows: Vec<usize> = trace.filtered(&filter).map(|e| e.press).collect();
+
+        assert_eq!(lows, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_step() {
+        let config = Config::new(EXAMPLE);
+        let trace = config.trace(2);
+        let filter = Filter::default();
+        let mut output = vec![];
+
+        // one step, finish the press, one step, quit
+        trace.step(&filter, "\np\n\nq\n".as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], "press 1 #0: button -low-> broadcaster");
+        assert_eq!(lines[7], "press 1 #7: con -low-> output");
+        assert_eq!(lines[8], "press 2 #0: button -low-> broadcaster");
+        assert_eq!(lines[9], "press 2 #1: broadcaster -low-> a");
+    }
+}
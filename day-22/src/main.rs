@@ -0,0 +1,209 @@
+use std::{ time::Instant, fs, collections::{ HashMap, HashSet, VecDeque } };
+use lib::{ get_part, Point, Point3d };
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Brick {
+    // lowest corner
+    start: Point3d,
+    // highest corner
+    end: Point3d,
+}
+
+fn parse_point(s: &str) -> Point3d {
+    let nums: Vec<isize> = s
+        .split(',')
+        .map(|n| n.trim().parse().expect("coordinate to be a number"))
+        .collect();
+
+    Point3d { x: nums[0], y: nums[1], z: nums[2] }
+}
+
+impl Brick {
+    fn new(line: &str) -> Self {
+        let (a, b) = line.split_once('~').expect("expected '~'");
+        let (a, b) = (parse_point(a), parse_point(b));
+
+        // not sure the input is always ordered, so make sure it is
+        Self {
+            start: Point3d { x: a.x.min(b.x), y: a.y.min(b.y), z: a.z.min(b.z) },
+            end: Point3d { x: a.x.max(b.x), y: a.y.max(b.y), z: a.z.max(b.z) },
+        }
+    }
+
+    // every x,y cell that this brick sits over
+    fn footprint(&self) -> Vec<Point> {
+        let mut cells = vec![];
+
+        for x in self.start.x..=self.end.x {
+            for y in self.start.y..=self.end.y {
+                cells.push(Point { x, y });
+            }
+        }
+
+        cells
+    }
+
+    fn height(&self) -> isize {
+        self.end.z - self.start.z
+    }
+}
+
+struct Stack {
+    // settled bricks, ordered from the ground up
+    bricks: Vec<Brick>,
+    // bricks resting directly on top of each brick
+    supports: Vec<HashSet<usize>>,
+    // bricks directly underneath each brick
+    supported_by: Vec<HashSet<usize>>,
+}
+
+impl Stack {
+    fn new(contents: &str) -> Self {
+        let mut bricks: Vec<Brick> = contents.lines().map(Brick::new).collect();
+
+        // drop the lowest ones first
+        bricks.sort_by_key(|b| b.start.z);
+
+        let mut supports = vec![HashSet::new(); bricks.len()];
+        let mut supported_by = vec![HashSet::new(); bricks.len()];
+        // highest z (and which brick is there) for each x,y
+        let mut tops: HashMap<Point, (isize, usize)> = HashMap::new();
+
+        for i in 0..bricks.len() {
+            let footprint = bricks[i].footprint();
+
+            // the ground is at 0
+            let floor = footprint
+                .iter()
+                .filter_map(|p| tops.get(p))
+                .map(|(z, _)| *z)
+                .max()
+                .unwrap_or(0);
+
+            let height = bricks[i].height();
+            bricks[i].start.z = floor + 1;
+            bricks[i].end.z = floor + 1 + height;
+
+            for p in footprint {
+                if let Some((z, below)) = tops.get(&p) {
+                    if *z == floor {
+                        supports[*below].insert(i);
+                        supported_by[i].insert(*below);
+                    }
+                }
+
+                tops.insert(p, (bricks[i].end.z, i));
+            }
+        }
+
+        Self { bricks, supports, supported_by }
+    }
+
+    // a brick is safe if everything it holds up has another brick holding it
+    fn safe_to_disintegrate(&self) -> usize {
+        (0..self.bricks.len())
+            .filter(|i| {
+                self.supports[*i]
+                    .iter()
+                    .all(|above| self.supported_by[*above].len() > 1)
+            })
+            .count()
+    }
+
+    // how many other bricks fall if this one is removed
+    fn chain_reaction(&self, i: usize) -> usize {
+        let mut fallen = HashSet::from([i]);
+        let mut queue = VecDeque::from([i]);
+
+        while let Some(cur) = queue.pop_front() {
+            for above in self.supports[cur].iter() {
+                if fallen.contains(above) {
+                    continue;
+                }
+
+                if self.supported_by[*above].iter().all(|b| fallen.contains(b)) {
+                    fallen.insert(*above);
+                    queue.push_back(*above);
+                }
+            }
+        }
+
+        // don't count the one we removed
+        fallen.len() - 1
+    }
+
+    fn total_falling(&self) -> usize {
+        (0..self.bricks.len()).map(|i| self.chain_reaction(i)).sum()
+    }
+}
+
+fn part_one(stack: &Stack) -> usize {
+    stack.safe_to_disintegrate()
+}
+
+fn part_two(stack: &Stack) -> usize {
+    stack.total_falling()
+}
+
+fn main() {
+    let (one, two) = get_part();
+    let start = Instant::now();
+    let contents = fs::read_to_string("./src/input.txt").unwrap();
+    let stack = Stack::new(&contents);
+
+    if one {
+        let now = Instant::now();
+        let ans = part_one(&stack);
+        println!("Part one: {:?} {:?}", ans, now.elapsed());
+    }
+
+    if two {
+        let now = Instant::now();
+        let ans = part_two(&stack);
+        println!("Part two: {:?} {:?}", ans, now.elapsed());
+    }
+
+    println!("Time: {:?}", start.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("./example.txt");
+
+    #[test]
+    fn test_settle() {
+        let stack = Stack::new(EXAMPLE);
+
+        // A is on the ground; G falls down onto F
+        assert_eq!(stack.bricks[0].start.z, 1);
+        assert_eq!(stack.bricks[6].start.z, 5);
+        assert_eq!(stack.bricks[6].end.z, 6);
+
+        // A holds up B and C
+        assert_eq!(stack.supports[0], HashSet::from([1, 2]));
+        // D is held up by B and C
+        assert_eq!(stack.supported_by[3], HashSet::from([1, 2]));
+    }
+
+    #[test]
+    fn test_part_one() {
+        let stack = Stack::new(EXAMPLE);
+        let ans = part_one(&stack);
+
+        assert_eq!(ans, 5);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let stack = Stack::new(EXAMPLE);
+
+        assert_eq!(stack.chain_reaction(0), 6);
+        assert_eq!(stack.chain_reaction(5), 1);
+
+        let ans = part_two(&stack);
+
+        assert_eq!(ans, 7);
+    }
+}
@@ -3,11 +3,13 @@ use std::{
     fs,
     str::FromStr,
     io::Error,
+    ops::{ Add, Sub, Mul },
     vec,
 };
-use lib::get_part;
+use lib::{ get_part, Rational };
 
-struct Vec3d(isize, isize, isize);
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Vec3d(i128, i128, i128);
 
 impl FromStr for Vec3d {
     type Err = Error;
@@ -16,7 +18,7 @@ impl FromStr for Vec3d {
         let pos = s
             .split(", ")
             .map(|x| {
-                let num = x.trim().parse::<isize>();
+                let num = x.trim().parse::<i128>();
 
                 if num.is_err() {
                     panic!("what is this? {}", x);
@@ -24,71 +26,141 @@ impl FromStr for Vec3d {
 
                 num.unwrap()
             })
-            .collect::<Vec<isize>>();
+            .collect::<Vec<i128>>();
 
         Result::Ok(Self(pos[0], pos[1], pos[2]))
     }
 }
 
+impl Add for Vec3d {
+    type Output = Vec3d;
+
+    fn add(self, other: Vec3d) -> Vec3d {
+        Vec3d(self.0 + other.0, self.1 + other.1, self.2 + other.2)
+    }
+}
+
+impl Sub for Vec3d {
+    type Output = Vec3d;
+
+    fn sub(self, other: Vec3d) -> Vec3d {
+        Vec3d(self.0 - other.0, self.1 - other.1, self.2 - other.2)
+    }
+}
+
+impl Mul<i128> for Vec3d {
+    type Output = Vec3d;
+
+    fn mul(self, n: i128) -> Vec3d {
+        Vec3d(self.0 * n, self.1 * n, self.2 * n)
+    }
+}
+
+// only part one is in 2d
+#[allow(dead_code)]
+impl Vec3d {
+    fn dot(&self, other: &Vec3d) -> i128 {
+        self.0 * other.0 + self.1 * other.1 + self.2 * other.2
+    }
+
+    fn cross(&self, other: &Vec3d) -> Vec3d {
+        Vec3d(
+            self.1 * other.2 - self.2 * other.1,
+            self.2 * other.0 - self.0 * other.2,
+            self.0 * other.1 - self.1 * other.0,
+        )
+    }
+
+    // just the z of the cross product, for the xy plane
+    fn cross_2d(&self, other: &Vec3d) -> i128 {
+        self.0 * other.1 - self.1 * other.0
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Vec3d(0, 0, 0)
+    }
+}
+
+// the time each stone gets to the place where their paths cross
+type Crossing = (Rational, Rational);
+
 struct Hailstone {
     position: Vec3d,
     velocity: Vec3d,
-    // dang
-    slope: f64,
-    intercept: f64,
 }
 
 impl Hailstone {
     fn new(position: Vec3d, velocity: Vec3d) -> Self {
-        let (x1, y1, z1) = (position.0, position.1, position.2);
-        let (x2, y2, _z2) = (x1 + velocity.0, y1 + velocity.1, z1 + velocity.2);
+        Self { position, velocity }
+    }
 
-        // TODO: this is 2d
-        let slope = ((y2 - y1) as f64) / ((x2 - x1) as f64);
+    // where the stone is at a time, as (x, y, z) over a shared den
+    fn at(&self, t: &Rational) -> (Vec3d, i128) {
+        (self.position * t.denom() + self.velocity * t.numer(), t.denom())
+    }
 
-        // TODO: this is 2d
-        let intercept = (y1 as f64) - slope * (x1 as f64);
+    // ignoring z
+    fn intersects_2d(&self, b: &Hailstone) -> Result<Crossing, &str> {
+        let div = self.velocity.cross_2d(&b.velocity);
+        let gap = b.position - self.position;
 
-        Self {
-            position,
-            velocity,
-            slope,
-            intercept,
+        if div == 0 {
+            if gap.cross_2d(&self.velocity) == 0 {
+                return Err("same path");
+            }
+
+            return Err("parallel");
         }
+
+        Ok((
+            Rational::new(gap.cross_2d(&b.velocity), div),
+            Rational::new(gap.cross_2d(&self.velocity), div),
+        ))
     }
 
-    // TODO: 2d
-    // and wow f64
-    fn intersects(&self, b: &Hailstone) -> Result<(f64, f64, f64), &str> {
-        // x0​= (b2​−b1) / (a1​−a2)
-        // ​​y0​= a1*x0+b1​
-        let div: f64 = (self.slope - b.slope) as f64;
+    #[allow(dead_code)]
+    fn intersects_3d(&self, b: &Hailstone) -> Result<Crossing, &str> {
+        let normal = self.velocity.cross(&b.velocity);
+        let gap = b.position - self.position;
 
-        if div == (0 as f64) {
-            return Err("divide by zero");
-        }
+        if normal.is_zero() {
+            if gap.cross(&self.velocity).is_zero() {
+                return Err("same path");
+            }
 
-        if b.slope == self.slope {
             return Err("parallel");
         }
 
-        let x = ((b.intercept - self.intercept) as f64) / div;
-        let y = (self.slope as f64) * x + (self.intercept as f64);
+        // lines that aren't on the same plane go past each other
+        if gap.dot(&normal) != 0 {
+            return Err("skew");
+        }
+
+        let div = normal.dot(&normal);
 
-        return Ok((x, y, 0 as f64));
+        Ok((
+            Rational::new(gap.cross(&b.velocity).dot(&normal), div),
+            Rational::new(gap.cross(&self.velocity).dot(&normal), div),
+        ))
     }
 
-    fn is_past(&self, point: (f64, f64, f64)) -> bool {
-        let (x, y) = (self.position.0 as f64, self.position.1 as f64);
-        let (vx, vy) = (self.velocity.0 as f64, self.velocity.1 as f64);
+    // (when, distance squared) for the stones at their closest, from now on
+    #[allow(dead_code)]
+    fn closest_approach(&self, b: &Hailstone) -> (Rational, Rational) {
+        let gap = b.position - self.position;
+        let closing = b.velocity - self.velocity;
+        let speed = closing.dot(&closing);
+        let toward = gap.dot(&closing);
+
+        // already as close as they'll get
+        if speed == 0 || toward >= 0 {
+            return (Rational::zero(), Rational::from(gap.dot(&gap)));
+        }
 
-        #[allow(unused_parens)]
-        return (
-            (vx < 0.0 && point.0 > x) ||
-            (vx > 0.0 && point.0 < x) ||
-            (vy < 0.0 && point.1 > y) ||
-            (vy > 0.0 && point.1 < y)
-        );
+        (
+            Rational::new(-toward, speed),
+            Rational::new(gap.dot(&gap) * speed - toward * toward, speed),
+        )
     }
 }
 
@@ -110,41 +182,27 @@ fn parse_hailstones(contents: &str) -> Vec<Hailstone> {
 
 fn get_intersecting_count(
     hailstones: &Vec<Hailstone>,
-    boundary: (f64, f64)
+    boundary: (i128, i128)
 ) -> usize {
     let mut count = 0;
-    let r = boundary.0..boundary.1;
 
     for (i, h1) in hailstones.iter().enumerate() {
-        for (j, h2) in hailstones.iter().enumerate() {
-            if j <= i {
-                // already seen this hailstone
+        for h2 in hailstones.iter().skip(i + 1) {
+            let (t1, t2) = match h1.intersects_2d(h2) {
+                Ok(times) => times,
+                Err(_) => continue,
+            };
+
+            // crossed in the past
+            if t1.is_negative() || t2.is_negative() {
                 continue;
             }
-            let intersection = h1.intersects(h2);
-
-            if let Ok(intersection) = intersection {
-                if r.contains(&intersection.0) && r.contains(&intersection.1) {
-                    if !h1.is_past(intersection) && !h2.is_past(intersection) {
-                        count += 1;
-                        // println!("Yes: {}, {}, {:?}", i, j, (
-                        //     intersection.0,
-                        //     intersection.1,
-                        // ));
-                    } else {
-                        // println!("No (past): {}, {}, {:?}", i, j, (
-                        //     intersection.0,
-                        //     intersection.1,
-                        // ));
-                    }
-                } else {
-                    // println!("No (contains): {}, {}, {:?}", i, j, (
-                    //     intersection.0,
-                    //     intersection.1,
-                    // ));
-                }
-            } else {
-                // println!("No: {}, {}, {}", i, j, intersection.unwrap_err());
+
+            let (point, den) = h1.at(&t1);
+            let inside = |n: i128| boundary.0 * den <= n && n <= boundary.1 * den;
+
+            if inside(point.0) && inside(point.1) {
+                count += 1;
             }
         }
     }
@@ -153,8 +211,8 @@ fn get_intersecting_count(
 }
 
 fn part_one(hailstones: &Vec<Hailstone>) -> usize {
-    let boundary = (200000000000000., 400000000000000.);
-    
+    let boundary = (200000000000000, 400000000000000);
+
     get_intersecting_count(hailstones, boundary)
 }
 
@@ -191,27 +249,71 @@ mod tests {
 
     #[test]
     fn test_intersects() {
-        let a = Hailstone {
-            intercept: 2.0,
-            slope: 3.0,
-            position: Vec3d(0, 0, 0),
-            velocity: Vec3d(0, 0, 0),
-        };
-        let b = Hailstone {
-            intercept: -9.0,
-            slope: 4.0,
-            position: Vec3d(0, 0, 0),
-            velocity: Vec3d(0, 0, 0),
-        };
-
-        assert_eq!(a.intersects(&b).unwrap(), (11.0, 35.0, 0.0));
+        let hailstones = parse_hailstones(EXAMPLE);
+        let (t1, t2) = hailstones[0].intersects_2d(&hailstones[1]).unwrap();
+
+        // 14.333, 15.333
+        assert_eq!((t1, t2), (Rational::new(7, 3), Rational::new(11, 3)));
+        assert_eq!(hailstones[0].at(&t1), (Vec3d(43, 46, 76), 3));
+
+        // crossed in the past for both
+        let (t1, t2) = hailstones[1].intersects_2d(&hailstones[4]).unwrap();
+        assert!(t1.is_negative() && t2.is_negative());
+
+        assert_eq!(hailstones[1].intersects_2d(&hailstones[2]).err(), Some("parallel"));
+    }
+
+    #[test]
+    fn test_near_parallel() {
+        // floats can't tell these slopes apart
+        let a = Hailstone::new(Vec3d(200000000000000, 200000000000000, 0), Vec3d(999_999_999, 1_000_000_000, 0));
+        let b = Hailstone::new(Vec3d(200000000000001, 200000000000000, 0), Vec3d(1_000_000_000, 1_000_000_001, 0));
+        let (t1, t2) = a.intersects_2d(&b).unwrap();
+        let (point, den) = a.at(&t1);
+
+        assert_eq!(b.at(&t2), (point, den));
+    }
+
+    #[test]
+    fn test_intersects_3d() {
+        let a = Hailstone::new(Vec3d(0, 0, 0), Vec3d(1, 1, 1));
+        let b = Hailstone::new(Vec3d(10, 0, 0), Vec3d(-2, 1, 1));
+        let (t1, t2) = a.intersects_3d(&b).unwrap();
+
+        assert_eq!(a.at(&t1), b.at(&t2));
+        assert_eq!(a.at(&t1), (Vec3d(10, 10, 10), 3));
+
+        let c = Hailstone::new(Vec3d(0, 0, 5), Vec3d(1, -1, 0));
+        assert_eq!(a.intersects_3d(&c).err(), Some("skew"));
+
+        let d = Hailstone::new(Vec3d(2, 2, 2), Vec3d(-3, -3, -3));
+        assert_eq!(a.intersects_3d(&d).err(), Some("same path"));
+    }
+
+    #[test]
+    fn test_closest_approach() {
+        let a = Hailstone::new(Vec3d(0, 0, 0), Vec3d(1, 0, 0));
+        let b = Hailstone::new(Vec3d(10, 3, 4), Vec3d(-1, 0, 0));
+
+        // they pass each other at t=5, 5 apart
+        assert_eq!(a.closest_approach(&b), (Rational::new(5, 1), Rational::new(25, 1)));
+
+        // heading apart, so now is as close as they get
+        let c = Hailstone::new(Vec3d(-1, 0, 0), Vec3d(-1, 0, 0));
+        assert_eq!(a.closest_approach(&c), (Rational::new(0, 1), Rational::new(1, 1)));
+
+        let hailstones = parse_hailstones(EXAMPLE);
+        let (when, distance) = hailstones[0].closest_approach(&hailstones[1]);
+
+        assert!(when.to_f64() > 0.0);
+        assert!(distance.to_f64() >= 0.0);
     }
 
     #[test]
     fn test_intersections() {
         let hailstones: Vec<Hailstone> = parse_hailstones(EXAMPLE);
 
-        let count = get_intersecting_count(&hailstones, (7.0, 27.0));
+        let count = get_intersecting_count(&hailstones, (7, 27));
 
         assert_eq!(count, 2);
     }
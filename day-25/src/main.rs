@@ -1,10 +1,11 @@
-use std::{ time::Instant, fs, collections::{HashMap, HashSet}, vec, hash::{Hasher, Hash} };
+use std::{ time::Instant, fs, env, fmt, collections::{ BTreeMap, HashMap, VecDeque }, vec };
 use lib::get_part;
 
-type Components<'a> = HashMap<&'a str, Vec<&'a str>>;
+// BTreeMap, so iterating it is the same every run
+type Components<'a> = BTreeMap<&'a str, Vec<&'a str>>;
 
-fn get_components(contents: &str) -> Components {
-    let mut components = HashMap::new();
+fn get_components(contents: &str) -> Components<'_> {
+    let mut components = BTreeMap::new();
     let lines = contents.lines();
 
     for line in lines {
@@ -32,163 +33,348 @@ fn get_components(contents: &str) -> Components {
     components
 }
 
-#[derive(Debug)]
-struct State<'a> {
-    current: &'a str,
-    visited: HashSet<&'a str>,
-    path: Vec<(&'a str, &'a str)>,
+// components, but with numbers instead of names
+struct Graph<'a> {
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, usize>,
+    // (a, b) for every wire, where a < b
+    edges: Vec<(usize, usize)>,
+    // (neighbour, edge index)
+    links: Vec<Vec<(usize, usize)>>,
 }
 
-fn bfs<'a>(akey: &'a str, bkey: &'a str, components: &'a Components) -> Vec<(&'a str, &'a str)> {
-    let start = State{
-        current: akey,
-        visited: HashSet::new(),
-        path: vec![]
-    };
+#[derive(Debug)]
+struct MinCut<'a> {
+    edges: Vec<(&'a str, &'a str)>,
+    // the side with the first name in it
+    group_a: Vec<&'a str>,
+    group_b: Vec<&'a str>,
+}
 
-    // TODO: try binary heap
-    let mut queue = vec![start];
+impl fmt::Display for MinCut<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let edges: Vec<String> = self.edges
+            .iter()
+            .map(|(a, b)| format!("{}/{}", a, b))
+            .collect();
 
-    while let Some(state) = queue.pop() {
-        let current = state.current;
+        writeln!(f, "cut ({}): {}", edges.len(), edges.join(" "))?;
+        writeln!(f, "a ({}): {}", self.group_a.len(), self.group_a.join(" "))?;
+        writeln!(f, "b ({}): {}", self.group_b.len(), self.group_b.join(" "))
+    }
+}
 
-        if state.visited.contains(current) {
-            continue;
-        }
+impl<'a> Graph<'a> {
+    fn new(components: &Components<'a>) -> Self {
+        // keys come out sorted, so the ids don't depend on the input order
+        let names: Vec<&str> = components.keys().copied().collect();
 
-        let links = components.get(&current).expect("current not in components");
+        let ids: HashMap<&str, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (*name, i))
+            .collect();
 
-        // ? can we check if any of these are in bkey's links?
-        for next in links {
-            let mut path = state.path.clone();
+        let mut edges = vec![];
 
-            path.push((current, *next));
+        for (a, name) in names.iter().enumerate() {
+            for link in components[name].iter() {
+                let b = ids[link];
 
-            if *next == bkey {
-                // done
-                return path;
+                // every wire is in there twice
+                if a < b {
+                    edges.push((a, b));
+                }
             }
+        }
 
-            let mut visited = state.visited.clone();
+        edges.sort();
+        edges.dedup();
 
-            visited.insert(current);
+        let mut links = vec![vec![]; names.len()];
 
-            queue.insert(0, State{
-                current: next,
-                path,
-                visited
-            })
+        for (i, (a, b)) in edges.iter().enumerate() {
+            links[*a].push((*b, i));
+            links[*b].push((*a, i));
         }
+
+        Self { names, ids, edges, links }
     }
 
-    return vec![];
-}
+    // smallest set of wires that splits the graph in two:
+    // any cut has to separate node 0 from *something*, so the
+    // smallest max flow from 0 to every other node is the min cut
+    fn min_cut(&self) -> Option<MinCut<'a>> {
+        if self.names.len() < 2 {
+            return None;
+        }
+
+        let mut best: Option<(usize, Vec<bool>)> = None;
+
+        for t in 1..self.names.len() {
+            // no need to find more flow than the best cut so far
+            let limit = best.as_ref().map_or(usize::MAX, |(size, _)| *size);
+            let (size, flow) = self.max_flow(0, t, limit);
+
+            if size < limit {
+                best = Some((size, self.reachable(0, &flow)));
+            }
+        }
 
-#[derive(Debug, Eq)]
-struct Edge<'a>(&'a str, &'a str);
+        let (_, side) = best?;
 
-impl<'a> Edge<'a> {
-    fn from(a: &'a str, b: &'a str) -> Self {
-        Self(a.min(b), a.max(b))
+        Some(self.cut_from_side(&side))
     }
-}
 
-impl PartialEq<Self> for Edge<'_> {
-    fn eq(&self, other: &Self) -> bool {
-        self.0.min(self.1) == other.0.min(other.1) &&
-        self.0.max(self.1) == other.0.max(other.1)
+    fn min_cut_size(&self) -> Option<usize> {
+        self.min_cut().map(|cut| cut.edges.len())
     }
-}
 
-impl Hash for Edge<'_> {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.0.min(self.1).hash(state);
-        self.0.max(self.1).hash(state);
+    // every way to split the graph by cutting `size` wires, as long as
+    // it's the smallest cut between node 0 and whatever ends up on the
+    // other side; when `size` is the min cut, that's all of the min cuts
+    fn min_cuts(&self, size: usize) -> Vec<MinCut<'a>> {
+        let mut sides: Vec<Vec<bool>> = vec![];
+
+        for t in 1..self.names.len() {
+            // one more, so we know if it's too big
+            let (flow_size, flow) = self.max_flow(0, t, size + 1);
+
+            if flow_size != size {
+                continue;
+            }
+
+            for side in self.closed_sides(t, &flow) {
+                if !sides.contains(&side) {
+                    sides.push(side);
+                }
+            }
+        }
+
+        let mut cuts: Vec<MinCut> = sides
+            .iter()
+            .map(|side| self.cut_from_side(side))
+            .collect();
+
+        cuts.sort_by(|a, b| a.edges.cmp(&b.edges));
+
+        cuts
     }
-}
 
-fn bfs_everywhere<'a>(components: &'a Components) -> Vec<(&'a str, &'a str)> {
-    let keys: Vec<&&str> = components.keys().collect();
-    let mut distances: HashMap<Edge, usize> = HashMap::new();
+    // connected groups left over once `removed` are cut
+    fn components_without(&self, removed: &[(&str, &str)]) -> Vec<Vec<&'a str>> {
+        let removed: Vec<(usize, usize)> = removed
+            .iter()
+            .map(|(a, b)| {
+                let (a, b) = (self.ids[a], self.ids[b]);
+
+                (a.min(b), a.max(b))
+            })
+            .collect();
+
+        let mut group_of = vec![None; self.names.len()];
+        let mut groups = vec![];
 
-    // TODO: we can cache bfs from a to each val
-    for (i, akey) in keys.iter().enumerate() {
-        for (j, bkey) in keys.iter().enumerate() {
-            if i == j {
+        for start in 0..self.names.len() {
+            if group_of[start].is_some() {
                 continue;
             }
-            // bfs
-            let path = bfs(akey, bkey, components);
 
-            for paths in path.iter() {
-                // sorting for hash
-                let v = Edge::from(paths.0, paths.1);
+            let mut group = vec![];
+            let mut stack = vec![start];
+
+            group_of[start] = Some(groups.len());
+
+            while let Some(cur) = stack.pop() {
+                group.push(self.names[cur]);
 
-                distances.entry(v).and_modify(|x| {
-                    *x += 1;
-                }).or_insert(1);
+                for (next, edge) in self.links[cur].iter() {
+                    if group_of[*next].is_some() || removed.contains(&self.edges[*edge]) {
+                        continue;
+                    }
+
+                    group_of[*next] = Some(groups.len());
+                    stack.push(*next);
+                }
             }
+
+            group.sort();
+            groups.push(group);
         }
+
+        groups
     }
 
-    println!("got distances: {}", &distances.len());
+    // unit capacity max flow (Edmonds-Karp), stopping once it hits `limit`
+    // +1 is flowing a -> b, -1 is b -> a
+    fn max_flow(&self, s: usize, t: usize, limit: usize) -> (usize, Vec<i8>) {
+        let mut flow = vec![0i8; self.edges.len()];
+        let mut total = 0;
 
-    let mut output: Vec<_> = distances.into_iter().collect();
-    
-    // return the biggest, first three
-    output.sort_by(|a, b| b.1.cmp(&a.1) );
+        while total < limit {
+            let parent = match self.augmenting_path(s, t, &flow) {
+                Some(parent) => parent,
+                None => break,
+            };
 
-    output.iter().take(3).map(|x| {
-        return (x.0.0, x.0.1);
-    }).collect::<Vec<(&str, &str)>>()
-}
+            // walk back from the sink, pushing one unit along the path
+            let mut cur = t;
+
+            while cur != s {
+                let (prev, edge) = parent[cur].expect("path back to source");
+
+                flow[edge] += if self.edges[edge].0 == prev { 1 } else { -1 };
+                cur = prev;
+            }
+
+            total += 1;
+        }
+
+        (total, flow)
+    }
 
-fn remove_and_count_groups(components: &Components, cuts: Vec<(&str, &str)>) -> usize {
-    let mut clone = components.clone();
+    // how much more can go from `from` along `edge`
+    fn room(&self, from: usize, edge: usize, flow: &[i8]) -> i8 {
+        if self.edges[edge].0 == from {
+            1 - flow[edge]
+        } else {
+            1 + flow[edge]
+        }
+    }
+
+    // bfs over wires that still have room
+    fn augmenting_path(
+        &self,
+        s: usize,
+        t: usize,
+        flow: &[i8]
+    ) -> Option<Vec<Option<(usize, usize)>>> {
+        let mut parent = vec![None; self.names.len()];
+        let mut seen = vec![false; self.names.len()];
+        let mut queue = VecDeque::from([s]);
+
+        seen[s] = true;
+
+        while let Some(cur) = queue.pop_front() {
+            if cur == t {
+                return Some(parent);
+            }
 
-    for cut in cuts.iter() {
-        clone.entry(cut.0).and_modify(|x| {
-            x.retain(|y| *y != cut.1);
-        });
+            for (next, edge) in self.links[cur].iter() {
+                if seen[*next] || self.room(cur, *edge, flow) <= 0 {
+                    continue;
+                }
 
-        clone.entry(cut.1).and_modify(|x| {
-            x.retain(|y| *y != cut.0);
-        });
+                seen[*next] = true;
+                parent[*next] = Some((cur, *edge));
+                queue.push_back(*next);
+            }
+        }
+
+        None
     }
 
-    // now count nodes in one group
-    let (one, _) = cuts.first().expect("we had a cut");
+    // everything `s` can still push flow to
+    fn reachable(&self, s: usize, flow: &[i8]) -> Vec<bool> {
+        self.closure(&[s], flow, false)
+    }
 
-    let mut queue = vec![one];
-    let mut seen = HashSet::new();
+    // everything reachable from `starts` along wires with room left,
+    // or, going `backwards`, everything that can reach them
+    fn closure(&self, starts: &[usize], flow: &[i8], backwards: bool) -> Vec<bool> {
+        let mut seen = vec![false; self.names.len()];
+        let mut stack = starts.to_vec();
 
-    while let Some(current) = queue.pop() {
-        if seen.contains(current) {
-            continue;
+        for s in starts {
+            seen[*s] = true;
         }
 
-        seen.insert(current);
+        while let Some(cur) = stack.pop() {
+            for (next, edge) in self.links[cur].iter() {
+                let room = if backwards {
+                    self.room(*next, *edge, flow)
+                } else {
+                    self.room(cur, *edge, flow)
+                };
 
-        let links = clone.get(current).expect("current not in components");
+                if seen[*next] || room <= 0 {
+                    continue;
+                }
 
-        for link in links {
-            if seen.contains(link) {
-                continue;
+                seen[*next] = true;
+                stack.push(*next);
             }
-            queue.push(link);
         }
+
+        seen
     }
 
-    let group_a = seen.len();
-    let group_b = clone.len() - group_a;
+    // every min 0-t cut is a set of nodes with 0 in it, t not in it, and
+    // nothing leaving it in the residual graph (Picard-Queyranne):
+    // pick an undecided node, then either take it and everything it
+    // reaches, or leave it out with everything that reaches it
+    fn closed_sides(&self, t: usize, flow: &[i8]) -> Vec<Vec<bool>> {
+        let mut found = vec![];
+        let inside = self.closure(&[0], flow, false);
+        let outside = self.closure(&[t], flow, true);
+
+        let mut stack = vec![(inside, outside)];
+
+        while let Some((inside, outside)) = stack.pop() {
+            let undecided = (0..self.names.len()).find(|i| !inside[*i] && !outside[*i]);
+
+            let node = match undecided {
+                Some(node) => node,
+                None => {
+                    found.push(inside);
+                    continue;
+                }
+            };
+
+            let mut with: Vec<usize> = (0..self.names.len()).filter(|i| inside[*i]).collect();
+            with.push(node);
 
-    return group_a * group_b;
+            let mut without: Vec<usize> = (0..self.names.len()).filter(|i| outside[*i]).collect();
+            without.push(node);
+
+            stack.push((self.closure(&with, flow, false), outside.clone()));
+            stack.push((inside, self.closure(&without, flow, true)));
+        }
+
+        found
+    }
+
+    fn cut_from_side(&self, side: &[bool]) -> MinCut<'a> {
+        let mut edges: Vec<(&str, &str)> = self.edges
+            .iter()
+            .filter(|(a, b)| side[*a] != side[*b])
+            .map(|(a, b)| (self.names[*a], self.names[*b]))
+            .collect();
+
+        edges.sort();
+
+        let group = |want: bool| {
+            self.names
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| side[*i] == want)
+                .map(|(_, name)| *name)
+                .collect::<Vec<&str>>()
+        };
+
+        MinCut { edges, group_a: group(true), group_b: group(false) }
+    }
 }
 
 fn part_one(components: &Components) -> usize {
-    let cuts = bfs_everywhere(&components);
-    
-    remove_and_count_groups(&components, cuts)
+    let graph = Graph::new(components);
+    let cut = graph.min_cut().expect("at least two components");
+
+    graph
+        .components_without(&cut.edges)
+        .iter()
+        .map(|group| group.len())
+        .product()
 }
 
 fn part_two() -> usize {
@@ -202,6 +388,15 @@ fn main() {
 
     let components = get_components(&contents);
 
+    if env::args().any(|arg| arg == "cuts") {
+        let graph = Graph::new(&components);
+        let size = graph.min_cut_size().expect("at least two components");
+
+        for cut in graph.min_cuts(size) {
+            println!("{}", cut);
+        }
+    }
+
     if one {
         let now = Instant::now();
         let ans = part_one(&components);
@@ -226,11 +421,145 @@ mod tests {
     #[test]
     fn test_cuts() {
         let components = get_components(EXAMPLE);
-        let cuts = bfs_everywhere(&components);
-        
+        let graph = Graph::new(&components);
+        let cut = graph.min_cut().unwrap();
+        let cuts = cut.edges;
+
+        assert_eq!(cuts.len(), 3);
         assert!(cuts.contains(&("jqt", "nvd")));
         assert!(cuts.contains(&("bvb", "cmg")));
         assert!(cuts.contains(&("hfx", "pzl")));
+
+        assert_eq!(cut.group_a.len(), 6);
+        assert_eq!(cut.group_b.len(), 9);
+        assert!(cut.group_a.contains(&"bvb"));
+        assert!(cut.group_b.contains(&"cmg"));
+    }
+
+    #[test]
+    fn test_min_cuts() {
+        let components = get_components(EXAMPLE);
+        let graph = Graph::new(&components);
+
+        assert_eq!(graph.min_cut_size(), Some(3));
+
+        // the puzzle's cut is the only one
+        let cuts = graph.min_cuts(3);
+
+        assert_eq!(cuts.len(), 1);
+        assert_eq!(cuts[0].edges, vec![("bvb", "cmg"), ("hfx", "pzl"), ("jqt", "nvd")]);
+
+        // nothing smaller
+        assert!(graph.min_cuts(2).is_empty());
+    }
+
+    #[test]
+    fn test_min_cuts_cycle() {
+        // cutting any two wires of a square splits it
+        let components = get_components("a: b d\nc: b d");
+        let graph = Graph::new(&components);
+
+        assert_eq!(graph.min_cut_size(), Some(2));
+
+        let cuts = graph.min_cuts(2);
+
+        // 4 choose 2 ways to cut two wires in a cycle of 4
+        assert_eq!(cuts.len(), 6);
+        assert!(cuts.iter().all(|cut| cut.edges.len() == 2));
+        assert!(cuts.iter().any(|cut| cut.group_a == vec!["a", "b"]));
+        assert!(cuts.iter().any(|cut| cut.group_a == vec!["a"]));
+    }
+
+    #[test]
+    fn test_components_without() {
+        let components = get_components(EXAMPLE);
+        let graph = Graph::new(&components);
+
+        assert_eq!(graph.components_without(&[]).len(), 1);
+
+        let groups = graph.components_without(&[("cmg", "bvb"), ("jqt", "nvd"), ("hfx", "pzl")]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0], vec!["bvb", "hfx", "jqt", "ntq", "rhn", "xhk"]);
+        assert_eq!(groups[1].len(), 9);
+
+        // cutting all of qnr's wires leaves it on its own
+        let groups = graph.components_without(&[("qnr", "nvd"), ("qnr", "cmg"), ("qnr", "rzs"), ("qnr", "frs")]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1], vec!["qnr"]);
+    }
+
+    #[test]
+    fn test_display() {
+        let components = get_components("a: b\nb: c");
+        let graph = Graph::new(&components);
+        let cuts = graph.min_cuts(1);
+
+        assert_eq!(cuts[0].to_string(), "cut (1): a/b\na (1): a\nb (2): b c\n");
+    }
+
+    // same wires, written out in a different order
+    fn shuffled(contents: &str, seed: usize) -> String {
+        let mut lines: Vec<String> = contents
+            .lines()
+            .map(|line| {
+                let (name, links) = line.split_once(": ").unwrap();
+                let mut links: Vec<&str> = links.split_whitespace().collect();
+
+                let len = links.len();
+                links.rotate_left(seed % len);
+
+                format!("{}: {}", name, links.join(" "))
+            })
+            .collect();
+
+        let len = lines.len();
+        lines.rotate_left(seed % len);
+
+        if seed % 2 == 1 {
+            lines.reverse();
+        }
+
+        lines.join("\n")
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let components = get_components(EXAMPLE);
+        let graph = Graph::new(&components);
+        let expected = graph.min_cut().unwrap().to_string();
+
+        for seed in 0..50 {
+            let contents = shuffled(EXAMPLE, seed);
+            let components = get_components(&contents);
+            let graph = Graph::new(&components);
+
+            assert_eq!(graph.min_cut().unwrap().to_string(), expected);
+            assert_eq!(part_one(&components), 54);
+        }
+    }
+
+    #[test]
+    fn test_deterministic_ties() {
+        // lots of min cuts to choose from, so it has to pick the same one
+        let square = "a: b d\nc: b d";
+        let components = get_components(square);
+        let graph = Graph::new(&components);
+        let expected = graph.min_cut().unwrap().to_string();
+        let all: Vec<String> = graph.min_cuts(2).iter().map(|c| c.to_string()).collect();
+
+        assert_eq!(expected, "cut (2): a/b a/d\na (1): a\nb (3): b c d\n");
+
+        for seed in 0..50 {
+            let contents = shuffled(square, seed);
+            let components = get_components(&contents);
+            let graph = Graph::new(&components);
+            let cuts: Vec<String> = graph.min_cuts(2).iter().map(|c| c.to_string()).collect();
+
+            assert_eq!(graph.min_cut().unwrap().to_string(), expected);
+            assert_eq!(cuts, all);
+        }
     }
 
     #[test]
@@ -1,5 +1,9 @@
 use std::{ env, ops::Add };
 
+mod rational;
+
+pub use rational::{ Rational, solve_linear, crt };
+
 pub fn get_part() -> (bool, bool) {
     let args = env::args().skip(1);
 
@@ -39,3 +43,21 @@ impl Add for Point {
         }
     }
 }
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, PartialOrd, Ord)]
+pub struct Point3d {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl Add for Point3d {
+    type Output = Point3d;
+    fn add(self, other: Point3d) -> Point3d {
+        Point3d {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+}
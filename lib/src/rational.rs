@@ -0,0 +1,458 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    iter::Sum,
+    ops::{ Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign },
+};
+
+// unsigned, since the gcd of i128::MIN and 0 doesn't fit in an i128
+fn gcd(a: i128, b: i128) -> u128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+// exact num / den, always in lowest terms with a positive den
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub fn new(num: i128, den: i128) -> Self {
+        Self::checked_new(num, den).expect("rational with a zero denominator")
+    }
+
+    // None for a zero denominator, or if it doesn't fit once it's reduced
+    pub fn checked_new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+
+        // reduce the sizes, then put the sign back on the top
+        let div = gcd(num, den);
+        let (top, bottom) = (num.unsigned_abs() / div, den.unsigned_abs() / div);
+        let num = if (num < 0) != (den < 0) {
+            0i128.checked_sub_unsigned(top)?
+        } else {
+            i128::try_from(top).ok()?
+        };
+
+        Some(Self { num, den: i128::try_from(bottom).ok()? })
+    }
+
+    pub fn zero() -> Self {
+        Self { num: 0, den: 1 }
+    }
+
+    pub fn one() -> Self {
+        Self { num: 1, den: 1 }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.num < 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn abs(&self) -> Self {
+        self.checked_abs().expect("rational overflow")
+    }
+
+    pub fn recip(&self) -> Self {
+        Self::new(self.den, self.num)
+    }
+
+    // rounds toward negative infinity
+    pub fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    // rounds toward positive infinity; doesn't negate, so i128::MIN is fine
+    pub fn ceil(&self) -> i128 {
+        self.floor() + (self.num.rem_euclid(self.den) != 0) as i128
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    // None for i128::MIN, which has no positive
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self { num: self.num.checked_neg()?, den: self.den })
+    }
+
+    pub fn checked_abs(&self) -> Option<Self> {
+        Some(Self { num: self.num.checked_abs()?, den: self.den })
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        // smaller numbers than multiplying the dens together; dens are positive, so it fits
+        let div = gcd(self.den, other.den) as i128;
+        let num = self.num
+            .checked_mul(other.den / div)?
+            .checked_add(other.num.checked_mul(self.den / div)?)?;
+
+        Self::checked_new(num, self.den.checked_mul(other.den / div)?)
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&Self { num: other.num.checked_neg()?, den: other.den })
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        // cross-cancel first, again to keep the numbers small; no bigger than a den
+        let a = (gcd(self.num, other.den) as i128).max(1);
+        let b = (gcd(other.num, self.den) as i128).max(1);
+
+        Self::checked_new(
+            (self.num / a).checked_mul(other.num / b)?,
+            (self.den / b).checked_mul(other.den / a)?,
+        )
+    }
+
+    // None when dividing by zero, too
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        self.checked_mul(&Self::checked_new(other.den, other.num)?)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Self { num: n, den: 1 }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl Ord for Rational {
+    // compares whole parts, then the flipped leftovers, so it can't overflow
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.floor(), other.floor());
+
+        if a != b {
+            return a.cmp(&b);
+        }
+
+        // both leftovers are in [0, 1), as numerators over each den
+        let (x, y) = (self.num.rem_euclid(self.den), other.num.rem_euclid(other.den));
+
+        match (x, y) {
+            (0, 0) => Ordering::Equal,
+            (0, _) => Ordering::Less,
+            (_, 0) => Ordering::Greater,
+            // a bigger leftover has a smaller reciprocal
+            _ => Self { num: other.den, den: y }.cmp(&Self { num: self.den, den: x }),
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, other: Rational) -> Rational {
+        self.checked_add(&other).expect("rational overflow")
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, other: Rational) -> Rational {
+        self.checked_sub(&other).expect("rational overflow")
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, other: Rational) -> Rational {
+        self.checked_mul(&other).expect("rational overflow")
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    fn div(self, other: Rational) -> Rational {
+        assert!(!other.is_zero(), "divide by zero");
+
+        self.checked_div(&other).expect("rational overflow")
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        self.checked_neg().expect("rational overflow")
+    }
+}
+
+impl AddAssign for Rational {
+    fn add_assign(&mut self, other: Rational) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Rational {
+    fn sub_assign(&mut self, other: Rational) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for Rational {
+    fn mul_assign(&mut self, other: Rational) {
+        *self = *self * other;
+    }
+}
+
+impl DivAssign for Rational {
+    fn div_assign(&mut self, other: Rational) {
+        *self = *self / other;
+    }
+}
+
+impl Sum for Rational {
+    fn sum<I: Iterator<Item = Rational>>(iter: I) -> Rational {
+        iter.fold(Rational::zero(), |acc, n| acc + n)
+    }
+}
+
+// solves a · x = b by gaussian elimination; None if there isn't exactly one answer
+pub fn solve_linear(a: &[Vec<Rational>], b: &[Rational]) -> Option<Vec<Rational>> {
+    let n = b.len();
+
+    assert!(a.len() == n && a.iter().all(|row| row.len() == n), "expected a square system");
+
+    // each row with its answer on the end
+    let mut rows: Vec<Vec<Rational>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, b)| {
+            let mut row = row.clone();
+            row.push(*b);
+            row
+        })
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n).find(|&r| !rows[r][col].is_zero())?;
+        rows.swap(col, pivot);
+
+        let lead = rows[col][col];
+
+        for cell in rows[col].iter_mut() {
+            *cell /= lead;
+        }
+
+        let pivot_row = rows[col].clone();
+
+        for (r, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+
+            if r == col || factor.is_zero() {
+                continue;
+            }
+
+            for (cell, p) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+                *cell -= *p * factor;
+            }
+        }
+    }
+
+    Some(rows.iter().map(|row| row[n]).collect())
+}
+
+// combine x = a (mod m) and x = b (mod n); m and n don't have to be coprime,
+// but then a and b have to agree. None if they don't (or it won't fit)
+pub fn crt((a, m): (i128, i128), (b, n): (i128, i128)) -> Option<(i128, i128)> {
+    let (g, p, _) = extended_gcd(m, n);
+
+    if (b - a) % g != 0 {
+        return None;
+    }
+
+    let lcm = (m / g).checked_mul(n)?;
+    // m * p = g (mod n), and keep everything below n / g before multiplying
+    let k = ((b - a) / g % (n / g)).checked_mul(p)? % (n / g);
+    let x = a.checked_add(m.checked_mul(k)?)?.rem_euclid(lcm);
+
+    Some((x, lcm))
+}
+
+// (gcd, x, y) where a * x + b * y = gcd
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return (a, 1, 0);
+    }
+
+    let (g, x, y) = extended_gcd(b, a % b);
+
+    (g, y, x - a / b * y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(num: i128, den: i128) -> Rational {
+        Rational::new(num, den)
+    }
+
+    #[test]
+    fn test_normalized() {
+        assert_eq!(r(6, -4), r(-3, 2));
+        assert_eq!(r(-6, -4).numer(), 3);
+        assert_eq!(r(-6, -4).denom(), 2);
+        assert_eq!(r(0, -5), Rational::zero());
+        assert_eq!(Rational::checked_new(1, 0), None);
+        assert_eq!(r(4, 2).to_string(), "2");
+        assert_eq!(r(-1, 3).to_string(), "-1/3");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(r(1, 2) + r(1, 3), r(5, 6));
+        assert_eq!(r(1, 2) - r(1, 3), r(1, 6));
+        assert_eq!(r(2, 3) * r(9, 4), r(3, 2));
+        assert_eq!(r(2, 3) / r(-4, 9), r(-3, 2));
+        assert_eq!(-r(2, 3), r(-2, 3));
+        assert_eq!(r(2, 3).recip(), r(3, 2));
+        assert_eq!([r(1, 2), r(1, 4), r(1, 4)].into_iter().sum::<Rational>(), Rational::one());
+
+        let mut n = Rational::from(3);
+        n += r(1, 2);
+        n *= r(2, 1);
+        n -= Rational::one();
+        n /= r(3, 1);
+        assert_eq!(n, r(2, 1));
+    }
+
+    #[test]
+    fn test_floor_ceil() {
+        assert_eq!((r(7, 2).floor(), r(7, 2).ceil()), (3, 4));
+        assert_eq!((r(-7, 2).floor(), r(-7, 2).ceil()), (-4, -3));
+        assert_eq!((r(6, 2).floor(), r(6, 2).ceil()), (3, 3));
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(r(1, 3) < r(1, 2));
+        assert!(r(-1, 2) < r(-1, 3));
+        assert!(r(5, 3) > r(3, 2));
+        assert_eq!(r(2, 4).cmp(&r(1, 2)), Ordering::Equal);
+
+        // too big to cross-multiply
+        let big = i128::MAX / 3;
+        assert!(r(big, big - 1) < r(big - 1, big - 2));
+
+        // floor * den is past i128::MIN
+        let small = r(i128::MIN + 1, 3);
+        assert_eq!(small.cmp(&small), Ordering::Equal);
+        assert!(small < r(i128::MIN + 2, 3));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let big = Rational::from(i128::MAX);
+
+        assert_eq!(big.checked_add(&Rational::one()), None);
+        assert_eq!(big.checked_mul(&r(2, 1)), None);
+        assert_eq!(big.checked_mul(&r(1, 2)), Some(r(i128::MAX, 2)));
+        assert_eq!(Rational::one().checked_div(&Rational::zero()), None);
+        // cancels before multiplying
+        assert_eq!(r(i128::MAX, 3).checked_mul(&r(3, i128::MAX)), Some(Rational::one()));
+
+        // reducing would need a positive i128::MIN
+        assert_eq!(Rational::checked_new(i128::MIN, -1), None);
+        assert_eq!(Rational::checked_new(0, i128::MIN), Some(Rational::zero()));
+        assert_eq!(Rational::checked_new(i128::MIN, i128::MIN), Some(Rational::one()));
+        assert_eq!(Rational::checked_new(i128::MIN, 2), Some(Rational::from(i128::MIN / 2)));
+
+        // i128::MIN has no positive
+        let min = Rational::from(i128::MIN);
+
+        assert_eq!(min.checked_neg(), None);
+        assert_eq!(min.checked_abs(), None);
+        assert_eq!(r(i128::MIN + 1, 1).checked_neg(), Some(Rational::from(i128::MAX)));
+        assert_eq!((min.floor(), min.ceil()), (i128::MIN, i128::MIN));
+        assert_eq!(r(i128::MIN, 3).ceil(), i128::MIN / 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "rational overflow")]
+    fn test_neg_overflow() {
+        let _ = -Rational::from(i128::MIN);
+    }
+
+    #[test]
+    #[should_panic(expected = "rational overflow")]
+    fn test_abs_overflow() {
+        Rational::from(i128::MIN).abs();
+    }
+
+    #[test]
+    fn test_solve_linear() {
+        let a = vec![
+            vec![r(2, 1), r(1, 1), r(-1, 1)],
+            vec![r(-3, 1), r(-1, 1), r(2, 1)],
+            vec![r(-2, 1), r(1, 1), r(2, 1)],
+        ];
+        let b = vec![r(8, 1), r(-11, 1), r(-3, 1)];
+
+        assert_eq!(solve_linear(&a, &b), Some(vec![r(2, 1), r(3, 1), r(-1, 1)]));
+
+        // needs a row swap, and has fractions
+        let a = vec![vec![r(0, 1), r(3, 1)], vec![r(2, 1), r(1, 1)]];
+        let b = vec![r(1, 1), r(1, 1)];
+        assert_eq!(solve_linear(&a, &b), Some(vec![r(1, 3), r(1, 3)]));
+
+        let singular = vec![vec![r(1, 1), r(2, 1)], vec![r(2, 1), r(4, 1)]];
+        assert_eq!(solve_linear(&singular, &b), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((0, 1), (2, 3)), Some((2, 3)));
+        assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+        // not coprime, but they agree
+        assert_eq!(crt((2, 4), (4, 6)), Some((10, 12)));
+        assert_eq!(crt((1, 4), (2, 6)), None);
+        // the lcm doesn't fit
+        assert_eq!(crt((0, i128::MAX), (1, i128::MAX - 1)), None);
+    }
+}